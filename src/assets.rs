@@ -1,18 +1,29 @@
 use bevy_asset_loader::prelude::*;
 
+use crate::card_loader::{CardCollection, CardCollectionLoader};
+use crate::encounter_loader::{Encounter, EncounterLoader};
 use crate::prelude::*;
+use crate::profile_loader::{AiProfileCollection, AiProfileLoader};
 
 pub struct AssetPlugin;
 
 impl Plugin for AssetPlugin {
     fn build(&self, app: &mut App) {
+        app.init_asset::<CardCollection>();
+        app.init_asset_loader::<CardCollectionLoader>();
+        app.init_asset::<AiProfileCollection>();
+        app.init_asset_loader::<AiProfileLoader>();
+        app.init_asset::<Encounter>();
+        app.init_asset_loader::<EncounterLoader>();
         app.add_loading_state(
             LoadingState::new(crate::MainState::Loading)
                 .continue_to_state(crate::MainState::TestingSetup)
                 .load_collection::<Fonts>()
                 .load_collection::<Icons>()
                 .load_collection::<Cards>()
-                .load_collection::<HealthBar>(),
+                .load_collection::<HealthBar>()
+                .load_collection::<Ai>()
+                .load_collection::<Encounters>(),
         );
     }
 }
@@ -45,8 +56,18 @@ pub struct Cards {
     pub base: Handle<Image>,
     #[asset(path = "Cards/back.png")]
     pub back: Handle<Image>,
-    #[asset(path = "Cards/placeholder.png")]
-    pub placeholder: Handle<Image>,
-    #[asset(path = "Cards/ghost.png")]
-    pub ghost: Handle<Image>,
+    #[asset(path = "Cards/definitions.cards.ron")]
+    pub definitions: Handle<CardCollection>,
+}
+
+#[derive(AssetCollection, Resource)]
+pub struct Ai {
+    #[asset(path = "Ai/profiles.ai.ron")]
+    pub profiles: Handle<AiProfileCollection>,
+}
+
+#[derive(AssetCollection, Resource)]
+pub struct Encounters {
+    #[asset(path = "Encounters/test.encounter.ron")]
+    pub test: Handle<Encounter>,
 }
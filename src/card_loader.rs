@@ -0,0 +1,86 @@
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, AsyncReadExt, LoadContext};
+use bevy::utils::{HashMap, HashSet};
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::combat::card::CardInfo;
+use crate::combat::card_text::rules_text;
+use crate::prelude::*;
+use crate::rules::cards::{CardGameplayInfo, CardId};
+
+#[derive(Asset, TypePath)]
+pub struct CardCollection(pub Vec<CardInfo>);
+
+#[derive(Deserialize)]
+struct CardDefinition {
+    id: CardId,
+    name: String,
+    art: String,
+    #[serde(default)]
+    flavor_text: Option<String>,
+    #[serde(default)]
+    token: bool,
+    gameplay: CardGameplayInfo,
+}
+
+#[derive(Default)]
+pub struct CardCollectionLoader;
+
+#[derive(Error, Debug)]
+pub enum CardLoadError {
+    #[error("Could not read card definitions: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid card definitions: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+    #[error("Card id {0:?} is used more than once")]
+    DuplicateId(CardId),
+}
+
+impl AssetLoader for CardCollectionLoader {
+    type Asset = CardCollection;
+    type Settings = ();
+    type Error = CardLoadError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let definitions: Vec<CardDefinition> = ron::de::from_bytes(&bytes)?;
+
+        let mut seen = HashSet::new();
+        for definition in &definitions {
+            if !seen.insert(definition.id.clone()) {
+                return Err(CardLoadError::DuplicateId(definition.id.clone()));
+            }
+        }
+
+        // Rules text can mention other cards, like the tokens a card summons
+        let names: HashMap<CardId, String> = definitions
+            .iter()
+            .map(|definition| (definition.id.clone(), definition.name.clone()))
+            .collect();
+
+        let cards = definitions
+            .into_iter()
+            .map(|definition| CardInfo {
+                id: definition.id,
+                img: load_context.load(definition.art),
+                rules_text: rules_text(&definition.gameplay, &names),
+                name: definition.name,
+                flavor_text: definition.flavor_text,
+                token: definition.token,
+                gameplay: definition.gameplay,
+            })
+            .collect();
+        Ok(CardCollection(cards))
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["cards.ron"]
+    }
+}
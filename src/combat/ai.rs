@@ -1,39 +1,737 @@
-use super::hand::{Hand, PlayCard};
-use super::{AllowedToPlay, PlayerReference, TurnState, WhosTurnIsIt};
+use bevy::tasks::{block_on, poll_once, AsyncComputeTaskPool, Task};
+use bevy::utils::HashMap;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::Deserialize;
+
+use super::mirror::{apply_actions, Match, TakeAction};
+use super::search::{search, SearchSettings};
+use super::{OpponentInfo, PlayerReference};
 use crate::prelude::*;
+use crate::profile_loader::AiProfileCollection;
+use crate::rng::GameRng;
+use crate::rules::cards::{Duration, Effect, Keyword, Trigger};
+use crate::rules::{Action, CardRef, CardState, GameState, Phase, Side};
 
 pub struct AiPlugin;
 
+#[derive(Default, Clone, Copy, Debug, Deserialize)]
+pub enum AiDifficulty {
+    #[default]
+    Normal,
+    Hard(SearchSettings),
+}
+
+// How an opponent plays, the weights are its play style
+#[derive(Default, Clone)]
+pub struct AiProfile {
+    pub difficulty: AiDifficulty,
+    pub weights: AiWeights,
+}
+
+// Every profile from the data files, by name
+#[derive(Resource)]
+pub struct AiProfiles(pub HashMap<String, AiProfile>);
+
+// The hard ai thinks on another thread
+#[derive(Resource)]
+struct PendingSearch(Task<Option<Action>>);
+
 impl Plugin for AiPlugin {
     fn build(&self, app: &mut App) {
+        app.add_systems(OnExit(MainState::Loading), create_ai_profiles);
+        app.add_systems(OnExit(MainState::Combat), cancel_search);
         app.add_systems(
-            OnEnter(TurnState::DrawCard),
-            do_very_smart_ai_thing.run_if(in_state(WhosTurnIsIt(PlayerReference::Ai))),
+            Update,
+            take_ai_turn
+                .before(apply_actions)
+                .run_if(resource_exists::<Match>)
+                .run_if(in_state(MainState::Combat)),
         );
     }
 }
 
-fn do_very_smart_ai_thing(
-    hands: Query<(&Hand, &PlayerReference)>,
-    cards: Query<(), With<AllowedToPlay>>,
-    mut play: EventWriter<PlayCard>,
+fn create_ai_profiles(
+    mut commands: Commands,
+    assets: Res<assets::Ai>,
+    collections: Res<Assets<AiProfileCollection>>,
 ) {
-    let Some((hand, _)) = hands
-        .iter()
-        .find(|(_, player)| **player == PlayerReference::Ai)
-    else {
-        return;
+    let profiles = collections
+        .get(&assets.profiles)
+        .map(|collection| collection.0.clone())
+        .unwrap_or_default();
+    commands.insert_resource(AiProfiles(profiles));
+}
+
+fn take_ai_turn(
+    mut commands: Commands,
+    game: Res<Match>,
+    opponent: Res<OpponentInfo>,
+    mut rng: ResMut<GameRng>,
+    pending: Option<ResMut<PendingSearch>>,
+    mut actions: EventWriter<TakeAction>,
+) {
+    let mut send = |chosen: Vec<Action>| {
+        actions.send_batch(chosen.into_iter().map(|action| TakeAction {
+            player: PlayerReference::Ai,
+            action,
+        }));
     };
 
-    for (index, card) in hand.0.iter().enumerate() {
-        // We are allowed to play the card
-        if cards.contains(*card) {
-            play.send(PlayCard {
-                player: PlayerReference::Ai,
-                card: *card,
-                hand_index: index,
+    // Nothing else can act while the ai decides, so the state hasn't changed since
+    if let Some(mut pending) = pending {
+        if let Some(chosen) = block_on(poll_once(&mut pending.0)) {
+            commands.remove_resource::<PendingSearch>();
+            send(chosen.into_iter().collect());
+        }
+        return;
+    }
+
+    let state = game.state();
+    if state.decider() != Some(Side::Ai) {
+        return;
+    }
+
+    match opponent.ai.difficulty {
+        AiDifficulty::Normal => send(choose_actions(state, Side::Ai, &opponent.ai.weights)),
+        // The search gets its own rng, so how long it thinks doesn't change the match
+        AiDifficulty::Hard(settings) => {
+            let state = state.clone();
+            let weights = opponent.ai.weights.clone();
+            let mut search_rng = ChaCha8Rng::seed_from_u64(rng.0.gen());
+            let task = AsyncComputeTaskPool::get().spawn(async move {
+                search(
+                    &state,
+                    Side::Ai,
+                    &settings,
+                    &|state, side| choose_actions(state, side, &weights),
+                    &mut search_rng,
+                )
             });
-            return;
+            commands.insert_resource(PendingSearch(task));
+        }
+    }
+}
+
+fn cancel_search(mut commands: Commands) {
+    commands.remove_resource::<PendingSearch>();
+}
+
+// Everything the side decides at this point of the turn, in the order it has to happen
+pub fn choose_actions(state: &GameState, side: Side, weights: &AiWeights) -> Vec<Action> {
+    match state.phase() {
+        Phase::PlayCreature => vec![choose_play(state, side, weights)],
+        Phase::SelectAttackers => {
+            let mut actions = choose_attackers(state, side, weights)
+                .into_iter()
+                .map(Action::ToggleAttacker)
+                .collect::<Vec<_>>();
+            actions.push(Action::ConfirmAttackers);
+            actions
+        }
+        Phase::SelectDefenders => {
+            let mut actions = choose_blockers(state, side, weights)
+                .into_iter()
+                .map(|(blocker, attacker)| Action::AssignBlocker {
+                    blocker,
+                    attacker: Some(attacker),
+                })
+                .collect::<Vec<_>>();
+            actions.push(Action::ConfirmBlockers);
+            actions
+        }
+        Phase::Discard => choose_discards(state, side, weights)
+            .into_iter()
+            .map(Action::Discard)
+            .collect(),
+    }
+}
+
+// How much the ai cares about each part of a play, tune these to change how it plays.
+// Play styles only list the weights they change, the rest keep their default.
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct AiWeights {
+    pub stats_per_crowd: f32,
+    pub keyword: f32,
+    pub board_deficit: f32,
+    pub unlocked_card: f32,
+    pub opponent_pressure: f32,
+    pub lethal: f32,
+    pub card_draw: f32,
+    pub healing: f32,
+    pub removal: f32,
+    pub token: f32,
+    pub aggression: f32,
+    pub caution_under_threat: f32,
+    pub crowd_loss: f32,
+    pub damage_prevented: f32,
+    // Plays worth less than this are not worth a card, so the ai passes instead
+    pub pass_below: f32,
+}
+
+impl Default for AiWeights {
+    fn default() -> Self {
+        Self {
+            stats_per_crowd: 1.0,
+            keyword: 0.5,
+            board_deficit: 0.3,
+            unlocked_card: 0.75,
+            opponent_pressure: 1.0,
+            lethal: 100.0,
+            card_draw: 1.0,
+            healing: 0.3,
+            removal: 1.0,
+            token: 1.5,
+            aggression: 1.0,
+            caution_under_threat: 2.0,
+            crowd_loss: 1.0,
+            damage_prevented: 0.5,
+            pass_below: 0.5,
+        }
+    }
+}
+
+struct Board {
+    own_power: u16,
+    enemy_power: u16,
+    missing_hp: u8,
+    opponent_hp: u8,
+    opponent_max_hp: u8,
+    crowd: u8,
+}
+
+impl Board {
+    fn new(state: &GameState, side: Side) -> Self {
+        let own = state.player(side);
+        let opponent = state.player(side.other());
+        let power_of = |side: Side| -> u16 {
+            state
+                .player(side)
+                .battlefield
+                .iter()
+                .map(|card| u16::from(state.power(*card)))
+                .sum()
+        };
+        Self {
+            own_power: power_of(side),
+            enemy_power: power_of(side.other()),
+            missing_hp: own.max_hp.saturating_sub(own.hp),
+            opponent_hp: opponent.hp,
+            opponent_max_hp: opponent.max_hp,
+            crowd: own.crowd(),
+        }
+    }
+
+    // Grows from 0 to 1 as the opponent gets closer to losing
+    fn opponent_danger(&self) -> f32 {
+        1.0 - f32::from(self.opponent_hp) / f32::from(self.opponent_max_hp.max(1))
+    }
+}
+
+struct Target {
+    power: u8,
+    hp: u8,
+    enemy: bool,
+}
+
+fn score_effect(
+    effect: &Effect,
+    target: Option<&Target>,
+    board: &Board,
+    weights: &AiWeights,
+) -> f32 {
+    match effect {
+        Effect::DamageOpponent(amount) if *amount >= board.opponent_hp => weights.lethal,
+        Effect::DamageOpponent(amount) => {
+            f32::from(*amount) * weights.opponent_pressure * (1.0 + board.opponent_danger())
+        }
+        Effect::HealController(amount) => {
+            f32::from((*amount).min(board.missing_hp)) * weights.healing
+        }
+        Effect::DrawCards(amount) => {
+            f32::from(u8::try_from(*amount).unwrap_or(u8::MAX)) * weights.card_draw
+        }
+        Effect::DamageCreature(amount, _) => {
+            let Some(target) = target else {
+                return 0.0;
+            };
+            let value = if *amount >= target.hp {
+                f32::from(target.power) + f32::from(target.hp)
+            } else {
+                f32::from(*amount) / 2.0
+            };
+            let value = value * weights.removal;
+            if target.enemy {
+                value
+            } else {
+                -value
+            }
+        }
+        Effect::ModifyCreature(modifier, _) => {
+            let Some(target) = target else {
+                return 0.0;
+            };
+            let turns = match modifier.duration {
+                Duration::EndOfTurn => 1,
+                Duration::Turns(turns) => turns,
+            };
+            let value = (f32::from(modifier.power) + f32::from(modifier.hp))
+                * f32::from(turns)
+                * weights.stats_per_crowd;
+            if target.enemy {
+                -value
+            } else {
+                value
+            }
+        }
+        Effect::SummonToken(_) => weights.token,
+    }
+}
+
+// None when the card can't be played right now
+fn score_card(
+    state: &GameState,
+    side: Side,
+    card: CardRef,
+    board: &Board,
+    weights: &AiWeights,
+) -> Option<(f32, Option<CardRef>)> {
+    if !state.is_playable(card) {
+        return None;
+    }
+    let gameplay = state.card(card).gameplay();
+
+    let target = gameplay
+        .triggers
+        .iter()
+        .filter(|triggered| triggered.trigger == Trigger::Play)
+        .find(|triggered| triggered.effect.target_rule().is_some())
+        .map(|triggered| state.pick_target(&triggered.effect, side))
+        .map_or(Some(None), |target| target.map(Some))?;
+    Some((
+        card_value(state, side, card, target, board, weights),
+        target,
+    ))
+}
+
+fn card_value(
+    state: &GameState,
+    side: Side,
+    card: CardRef,
+    target: Option<CardRef>,
+    board: &Board,
+    weights: &AiWeights,
+) -> f32 {
+    let gameplay = state.card(card).gameplay();
+    let target_view = target.map(|target| Target {
+        power: state.power(target),
+        hp: state.hp(target),
+        enemy: state.card(target).owner != side,
+    });
+
+    let mut score = 0.0;
+    for triggered in &gameplay.triggers {
+        let value = score_effect(&triggered.effect, target_view.as_ref(), board, weights);
+        // Other triggers might never happen
+        score += if triggered.trigger == Trigger::Play {
+            value
+        } else {
+            value / 2.0
+        };
+    }
+
+    if !state.card(card).is_spell() {
+        let power = state.power(card);
+        let strength = f32::from(power) + f32::from(state.max_hp(card));
+        score += weights.stats_per_crowd * strength / (f32::from(state.cast_cost(card)) + 1.0);
+        score +=
+            weights.keyword * f32::from(u8::try_from(gameplay.keywords.len()).unwrap_or(u8::MAX));
+        score +=
+            weights.board_deficit * f32::from(board.enemy_power.saturating_sub(board.own_power));
+        score += weights.opponent_pressure * f32::from(power) * board.opponent_danger();
+
+        let unlocked = state
+            .player(side)
+            .hand
+            .iter()
+            .filter(|other| **other != card)
+            .filter(|other| {
+                !state.can_afford_with_crowd(**other, board.crowd)
+                    && state.can_afford_with_crowd(**other, board.crowd.saturating_add(1))
+            })
+            .count();
+        score += weights.unlocked_card * f32::from(u8::try_from(unlocked).unwrap_or(u8::MAX));
+    }
+
+    score
+}
+
+fn choose_play(state: &GameState, side: Side, weights: &AiWeights) -> Action {
+    let own = state.player(side);
+    let board = Board::new(state, side);
+
+    // Ties go to the first card
+    let mut best: Option<(CardRef, f32, Option<CardRef>)> = None;
+    for card in &own.hand {
+        let Some((score, target)) = score_card(state, side, *card, &board, weights) else {
+            continue;
+        };
+        if best.as_ref().is_none_or(|(_, best, _)| score > *best) {
+            best = Some((*card, score, target));
+        }
+    }
+
+    match best {
+        Some((card, score, target)) if score >= weights.pass_below => Action::Play { card, target },
+        _ => Action::Pass,
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Fighter<'a> {
+    card: CardRef,
+    power: u8,
+    hp: u8,
+    minimum: u8,
+    state: &'a CardState,
+}
+
+impl Fighter<'_> {
+    fn value(&self) -> f32 {
+        f32::from(self.power) + f32::from(self.hp)
+    }
+
+    const fn kills(&self, other: &Fighter) -> bool {
+        self.power >= other.hp
+    }
+
+    fn has(&self, keyword: Keyword) -> bool {
+        self.state.has(keyword)
+    }
+}
+
+fn fighter(state: &GameState, card: CardRef) -> Fighter<'_> {
+    Fighter {
+        card,
+        power: state.power(card),
+        hp: state.hp(card),
+        minimum: state.card(card).gameplay().minimum_crowd,
+        state: state.card(card),
+    }
+}
+
+fn fighters(state: &GameState, side: Side) -> Vec<Fighter<'_>> {
+    state
+        .player(side)
+        .battlefield
+        .iter()
+        .map(|card| fighter(state, *card))
+        .collect()
+}
+
+// Creatures that leave when the lost one shrinks the crowd below their minimum
+fn crowd_loss(side: &[Fighter], lost: CardRef, crowd: u8) -> f32 {
+    let crowd = crowd.saturating_sub(1);
+    side.iter()
+        .filter(|fighter| fighter.card != lost && fighter.minimum > crowd)
+        .map(Fighter::value)
+        .sum()
+}
+
+// Whether the attack kills even if every blocker stops the biggest attacker it can
+fn is_lethal(attackers: &[Fighter], blockers: usize, hp: u8) -> bool {
+    let (unblockable, mut blockable): (Vec<&Fighter>, Vec<&Fighter>) = attackers
+        .iter()
+        .partition(|attacker| attacker.has(Keyword::Unblockable));
+    blockable.sort_by_key(|attacker| std::cmp::Reverse(attacker.power));
+
+    let through: u16 = unblockable
+        .iter()
+        .chain(blockable.iter().skip(blockers))
+        .map(|attacker| u16::from(attacker.power))
+        .sum();
+    through >= u16::from(hp)
+}
+
+// The most the ai can lose to the best block against this attacker
+fn worst_block(
+    attacker: &Fighter,
+    enemies: &[Fighter],
+    own: &[Fighter],
+    crowd: u8,
+    weights: &AiWeights,
+) -> f32 {
+    if attacker.has(Keyword::Unblockable) {
+        return 0.0;
+    }
+
+    enemies
+        .iter()
+        .filter(|blocker| blocker.kills(attacker))
+        .map(|blocker| {
+            let traded = if attacker.kills(blocker) {
+                blocker.value()
+            } else {
+                0.0
+            };
+            attacker.value() + weights.crowd_loss * crowd_loss(own, attacker.card, crowd) - traded
+        })
+        .fold(0.0, f32::max)
+}
+
+fn choose_attackers(state: &GameState, side: Side, weights: &AiWeights) -> Vec<CardRef> {
+    let own = fighters(state, side);
+    let enemies = fighters(state, side.other());
+    let own_hp = state.player(side).hp;
+    let crowd = state.player(side).crowd();
+
+    let lethal = is_lethal(&own, enemies.len(), state.player(side.other()).hp);
+
+    // Creatures lost now can't block the opponent's next attack
+    let enemy_power: u16 = enemies.iter().map(|enemy| u16::from(enemy.power)).sum();
+    let caution = if enemy_power >= u16::from(own_hp) {
+        weights.caution_under_threat
+    } else {
+        1.0
+    };
+
+    own.iter()
+        .filter(|attacker| {
+            let gain = f32::from(attacker.power) * weights.aggression;
+            let risk = worst_block(attacker, &enemies, &own, crowd, weights) * caution;
+            lethal || gain >= risk
+        })
+        .map(|attacker| attacker.card)
+        .collect()
+}
+
+// Negative when the block costs more than it saves
+fn block_score(
+    attacker: &Fighter,
+    blocker: &Fighter,
+    own: &[Fighter],
+    crowd: u8,
+    danger: f32,
+    weights: &AiWeights,
+) -> f32 {
+    let mut score = 0.0;
+    if blocker.kills(attacker) {
+        score += attacker.value();
+    }
+    if attacker.kills(blocker) {
+        score -= blocker.value() + weights.crowd_loss * crowd_loss(own, blocker.card, crowd);
+    }
+    score + weights.damage_prevented * f32::from(prevented_damage(attacker, blocker)) * danger
+}
+
+fn prevented_damage(attacker: &Fighter, blocker: &Fighter) -> u8 {
+    if attacker.has(Keyword::Trample) {
+        attacker.power.min(blocker.hp)
+    } else {
+        attacker.power
+    }
+}
+
+fn choose_blockers(state: &GameState, side: Side, weights: &AiWeights) -> Vec<(CardRef, CardRef)> {
+    let own = fighters(state, side);
+    let mut available = own.clone();
+    let mut already_blocked = Vec::new();
+    let mut blocks = Vec::new();
+
+    // Provoking attackers have to be blocked first
+    let mut attacking = state
+        .attackers()
+        .iter()
+        .map(|attacker| fighter(state, *attacker))
+        .collect::<Vec<_>>();
+    attacking.sort_by_key(|attacker| {
+        (
+            !attacker.has(Keyword::Provoke),
+            std::cmp::Reverse(attacker.power),
+        )
+    });
+
+    let player = state.player(side);
+    let crowd = player.crowd();
+    let danger = 2.0 - f32::from(player.hp) / f32::from(player.max_hp.max(1));
+    let mut incoming: u16 = attacking
+        .iter()
+        .map(|attacker| u16::from(attacker.power))
+        .sum();
+
+    for attacker in &attacking {
+        if !state.can_block(attacker.card, &already_blocked) {
+            continue;
+        }
+
+        let mut best: Option<(usize, f32)> = None;
+        for (index, blocker) in available.iter().enumerate() {
+            let score = block_score(attacker, blocker, &own, crowd, danger, weights);
+            if best.is_none_or(|(_, best)| score > best) {
+                best = Some((index, score));
+            }
+        }
+
+        // Bad blocks are only worth it when the ai would lose otherwise
+        let lethal = incoming >= u16::from(player.hp);
+        let Some((index, _)) = best.filter(|(_, score)| *score > 0.0 || lethal) else {
+            continue;
+        };
+
+        let blocker = available.remove(index);
+        incoming = incoming.saturating_sub(u16::from(prevented_damage(attacker, &blocker)));
+        already_blocked.push(attacker.card);
+        blocks.push((blocker.card, attacker.card));
+    }
+
+    blocks
+}
+
+fn choose_discards(state: &GameState, side: Side, weights: &AiWeights) -> Vec<CardRef> {
+    let hand = &state.player(side).hand;
+    let board = Board::new(state, side);
+
+    // Throw away the cards worth the least, and the most expensive ones among equals
+    let mut ranked = hand
+        .iter()
+        .map(|card| {
+            let target = state
+                .card(*card)
+                .gameplay()
+                .triggers
+                .iter()
+                .find_map(|triggered| state.pick_target(&triggered.effect, side));
+            let value = card_value(state, side, *card, target, &board, weights);
+            (*card, value, state.cast_cost(*card))
+        })
+        .collect::<Vec<_>>();
+    ranked.sort_by(|(_, value, cast), (_, other_value, other_cast)| {
+        value.total_cmp(other_value).then(other_cast.cmp(cast))
+    });
+
+    let excess = hand.len().saturating_sub(state.rules().max_hand_size);
+    ranked
+        .into_iter()
+        .take(excess)
+        .map(|(card, _, _)| card)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{choose_attackers, choose_blockers, choose_discards, choose_play, AiWeights};
+    use crate::rules::cards::{CardLibrary, Effect, Keyword, TargetRule};
+    use crate::rules::testing::{creature, rng, spell, Zone};
+    use crate::rules::{Action, CardRef, GameState, Phase, RuleSet, Side};
+
+    fn new_state() -> GameState {
+        GameState::for_test(RuleSet::default(), CardLibrary::default())
+    }
+
+    // The player attacks with these, so the ai has to decide its blocks
+    fn attack(state: &mut GameState, attackers: &[CardRef]) {
+        state.set_turn(Side::Player, Phase::SelectAttackers);
+        for attacker in attackers {
+            assert!(state
+                .apply(Action::ToggleAttacker(*attacker), &mut rng())
+                .is_ok());
         }
+        assert!(state.apply(Action::ConfirmAttackers, &mut rng()).is_ok());
+    }
+
+    #[test]
+    fn plays_lethal_damage() {
+        let mut state = new_state();
+        state.set_hp(Side::Player, 3);
+        state.add_card(Side::Ai, Zone::Hand, creature(2, 2));
+        let burn = state.add_card(Side::Ai, Zone::Hand, spell(Effect::DamageOpponent(3)));
+
+        let play = choose_play(&state, Side::Ai, &AiWeights::default());
+        assert_eq!(
+            play,
+            Action::Play {
+                card: burn,
+                target: None
+            }
+        );
+    }
+
+    #[test]
+    fn passes_without_affordable_cards() {
+        let mut state = new_state();
+        state.add_card(Side::Ai, Zone::Hand, creature(5, 5).with_costs(3, 0));
+
+        let play = choose_play(&state, Side::Ai, &AiWeights::default());
+        assert_eq!(play, Action::Pass);
+    }
+
+    #[test]
+    fn discards_keep_useful_spells() {
+        let rules = RuleSet {
+            max_hand_size: 1,
+            ..RuleSet::default()
+        };
+        let mut state = GameState::for_test(rules, CardLibrary::default());
+        state.add_card(Side::Player, Zone::Battlefield, creature(3, 3));
+        let removal = state.add_card(
+            Side::Ai,
+            Zone::Hand,
+            spell(Effect::DamageCreature(3, TargetRule::Enemy)),
+        );
+        let weak = state.add_card(Side::Ai, Zone::Hand, creature(1, 1));
+
+        let discards = choose_discards(&state, Side::Ai, &AiWeights::default());
+        assert_eq!(discards, vec![weak]);
+        assert!(state.player(Side::Ai).hand.contains(&removal));
+    }
+
+    #[test]
+    fn holds_back_from_losing_trades() {
+        let mut state = new_state();
+        state.add_card(Side::Ai, Zone::Battlefield, creature(1, 1));
+        state.add_card(Side::Player, Zone::Battlefield, creature(4, 4));
+
+        let attackers = choose_attackers(&state, Side::Ai, &AiWeights::default());
+        assert!(attackers.is_empty());
+    }
+
+    #[test]
+    fn attacks_with_everything_for_lethal() {
+        let mut state = new_state();
+        state.set_hp(Side::Player, 2);
+        let first = state.add_card(Side::Ai, Zone::Battlefield, creature(2, 1));
+        let second = state.add_card(Side::Ai, Zone::Battlefield, creature(2, 1));
+        state.add_card(Side::Player, Zone::Battlefield, creature(4, 4));
+
+        let attackers = choose_attackers(&state, Side::Ai, &AiWeights::default());
+        assert_eq!(attackers, vec![first, second]);
+    }
+
+    #[test]
+    fn blocks_when_the_blocker_survives() {
+        let mut state = new_state();
+        let attacker = state.add_card(Side::Player, Zone::Battlefield, creature(2, 2));
+        let blocker = state.add_card(Side::Ai, Zone::Battlefield, creature(3, 3));
+        attack(&mut state, &[attacker]);
+
+        let blocks = choose_blockers(&state, Side::Ai, &AiWeights::default());
+        assert_eq!(blocks, vec![(blocker, attacker)]);
+    }
+
+    #[test]
+    fn blocks_provoking_attackers_first() {
+        let mut state = new_state();
+        let plain = state.add_card(Side::Player, Zone::Battlefield, creature(3, 3));
+        let provoking = state.add_card(
+            Side::Player,
+            Zone::Battlefield,
+            creature(1, 1).with_keyword(Keyword::Provoke),
+        );
+        let blocker = state.add_card(Side::Ai, Zone::Battlefield, creature(4, 4));
+        attack(&mut state, &[plain, provoking]);
+
+        let blocks = choose_blockers(&state, Side::Ai, &AiWeights::default());
+        assert_eq!(blocks, vec![(blocker, provoking)]);
     }
 }
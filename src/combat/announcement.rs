@@ -0,0 +1,74 @@
+use bevy::color::palettes::tailwind::AMBER_300;
+
+use crate::prelude::*;
+
+pub struct AnnouncementPlugin;
+
+#[derive(Event)]
+pub struct Announce(pub String);
+
+#[derive(Component)]
+struct Announcement(Timer);
+
+impl Plugin for AnnouncementPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Announce>();
+        app.add_systems(
+            Update,
+            (spawn_announcements, fade_announcements).run_if(in_state(MainState::Combat)),
+        );
+    }
+}
+
+#[allow(clippy::cast_precision_loss)] // There are only ever a few announcements at once
+fn spawn_announcements(
+    mut commands: Commands,
+    mut events: EventReader<Announce>,
+    existing: Query<(), With<Announcement>>,
+    fonts: Res<assets::Fonts>,
+) {
+    for (count, Announce(message)) in (existing.iter().count()..).zip(events.read()) {
+        commands.spawn((
+            Name::new("Announcement"),
+            StateScoped(MainState::Combat),
+            Announcement(Timer::from_seconds(2.5, TimerMode::Once)),
+            TextBundle {
+                text: Text::from_section(
+                    message.clone(),
+                    TextStyle {
+                        font: fonts.pixel.clone_weak(),
+                        font_size: 40.0,
+                        color: AMBER_300.into(),
+                    },
+                )
+                .with_justify(JustifyText::Center),
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Vw(100.0),
+                    top: Val::Percent(count as f32 * 6.0 + 30.0),
+                    ..default()
+                },
+                ..default()
+            },
+        ));
+    }
+}
+
+fn fade_announcements(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Announcement, &mut Text)>,
+    time: Res<Time>,
+) {
+    for (entity, mut announcement, mut text) in &mut query {
+        announcement.0.tick(time.delta());
+        if announcement.0.finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        let alpha = announcement.0.fraction_remaining().min(0.5) * 2.0;
+        for section in &mut text.sections {
+            section.style.color.set_alpha(alpha);
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use super::hovering::Hovered;
+use super::mirror::{Match, TakeAction};
+use super::{PlayerReference, TurnIcon, TurnState, WhosTurnIsIt};
+use crate::prelude::*;
+use crate::rules::{Action, Side};
+
+pub struct AttackPlugin;
+
+#[derive(Component)]
+pub struct Attacking;
+
+impl Plugin for AttackPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (handle_attacker_input, handle_confirm_input)
+                .run_if(in_state(WhosTurnIsIt(PlayerReference::Player)))
+                .run_if(in_state(TurnState::SelectAttackers)),
+        );
+    }
+}
+
+fn handle_attacker_input(
+    cards: Query<Entity, With<Hovered>>,
+    game: Res<Match>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    mut actions: EventWriter<TakeAction>,
+) {
+    if !mouse.just_pressed(MouseButton::Left) {
+        return;
+    }
+
+    let battlefield = &game.state().player(Side::Player).battlefield;
+    for card in cards.iter().filter_map(|entity| game.card(entity)) {
+        if battlefield.contains(&card) {
+            actions.send(TakeAction {
+                player: PlayerReference::Player,
+                action: Action::ToggleAttacker(card),
+            });
+        }
+    }
+}
+
+fn handle_confirm_input(
+    icon: Query<(), (With<TurnIcon>, With<Hovered>)>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    mut actions: EventWriter<TakeAction>,
+) {
+    if mouse.just_pressed(MouseButton::Left) && !icon.is_empty() {
+        actions.send(TakeAction {
+            player: PlayerReference::Player,
+            action: Action::ConfirmAttackers,
+        });
+    }
+}
@@ -1,10 +1,16 @@
-use super::card::{Card, ShowFront};
-use super::hand::PlayCard;
-use super::{Crowd, PlayerReference};
+use bevy::utils::HashMap;
+
+use super::announcement::Announce;
+use super::attack::Attacking;
+use super::card::Card;
+use super::defend::{Blocking, SelectedBlocker};
+use super::mirror::{Match, MatchEvent};
+use super::PlayerReference;
 use crate::position::{AxisAnchor, Relative, RelativeAxis};
 use crate::prelude::*;
+use crate::rules::GameEvent;
 
-#[derive(Component)]
+#[derive(Component, PartialEq, Eq)]
 pub struct BattleField(pub Vec<Entity>);
 
 pub struct BattlePlugin;
@@ -13,46 +19,38 @@ impl Plugin for BattlePlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(
             Update,
-            (
-                move_card_to_battlefield,
-                position_cards_in_battle,
-                update_crowd_value,
-            )
-                .run_if(in_state(MainState::Combat)),
+            (position_cards_in_battle, announce_abandoned).run_if(in_state(MainState::Combat)),
         );
     }
 }
 
-fn move_card_to_battlefield(
-    mut battlefields: Query<(&mut BattleField, &PlayerReference)>,
-    mut cards: Query<&mut ShowFront>,
-    mut play: EventReader<PlayCard>,
+// Only writes cards whose place changed, so nothing else sees them change every frame
+#[allow(clippy::cast_precision_loss)] // The hand should never be very large
+fn position_cards_in_battle(
+    battlefields: Query<(&BattleField, &PlayerReference)>,
+    mut cards: Query<
+        (
+            &mut Relative,
+            &mut Transform,
+            Has<Attacking>,
+            Option<&Blocking>,
+            Has<SelectedBlocker>,
+        ),
+        With<Card>,
+    >,
 ) {
-    for event in play.read() {
-        let Ok(mut card) = cards.get_mut(event.card) else {
-            return;
-        };
-        card.0 = true;
-
-        let Some((mut battlefield, _)) = battlefields
-            .iter_mut()
-            .find(|(_, player)| **player == event.player)
-        else {
-            return;
-        };
+    let card_spacing = 150.0;
 
-        battlefield.0.push(event.card);
+    // Blockers line up with the attacker they block, which is on the other battlefield
+    let mut columns = HashMap::new();
+    for (hand, _) in &battlefields {
+        let offset = hand.0.len() as f32 / 2.0 * card_spacing;
+        for (index, card) in hand.0.iter().enumerate() {
+            columns.insert(*card, index as f32 * card_spacing - offset);
+        }
     }
-}
 
-#[allow(clippy::cast_precision_loss)] // The hand should never be very large
-fn position_cards_in_battle(
-    battlefields: Query<(&BattleField, &PlayerReference), Changed<BattleField>>,
-    mut cards: Query<(&mut Relative, &mut Transform), With<Card>>,
-) {
     for (hand, player) in &battlefields {
-        let card_spacing = 150.0;
-
         let y_level = if *player == PlayerReference::Player {
             470.0
         } else {
@@ -64,39 +62,60 @@ fn position_cards_in_battle(
             AxisAnchor::Pos
         };
 
-        let offset = hand.0.len() as f32 / 2.0 * card_spacing;
-        for (index, card) in hand.0.iter().enumerate().rev() {
-            let Ok((mut rel, mut trans)) = cards.get_mut(*card) else {
+        for (index, card) in hand.0.iter().enumerate() {
+            let Ok((mut rel, mut trans, attacking, blocking, selected)) = cards.get_mut(*card)
+            else {
                 continue;
             };
 
-            let position = index as f32 * card_spacing - offset;
-            trans.scale = Vec3::new(0.5, 0.5, 1.0);
-            trans.translation.z = index as f32 * 10.0;
-            *rel = Relative {
+            let forward = if attacking || blocking.is_some() {
+                40.0
+            } else if selected {
+                20.0
+            } else {
+                0.0
+            };
+
+            let mut position = columns.get(card).copied().unwrap_or_default();
+            let mut z = index as f32 * 10.0;
+            if let Some(column) = blocking.and_then(|blocking| columns.get(&blocking.0)) {
+                position = *column;
+                z += 100.0;
+            }
+
+            rel.set_if_neq(Relative {
                 x: Some(RelativeAxis {
                     anchor: AxisAnchor::Center,
                     amount: position,
                 }),
                 y: Some(RelativeAxis {
                     anchor,
-                    amount: y_level,
+                    amount: y_level + forward,
                 }),
-            }
+            });
+            let placed = Transform {
+                translation: trans.translation.with_z(z),
+                scale: Vec3::new(0.5, 0.5, 1.0),
+                ..*trans
+            };
+            trans.set_if_neq(placed);
         }
     }
 }
 
-#[allow(clippy::cast_possible_truncation)]
-fn update_crowd_value(
-    mut players: Query<(&mut Crowd, &PlayerReference)>,
-    battlefields: Query<(&BattleField, &PlayerReference), Changed<BattleField>>,
+fn announce_abandoned(
+    game: Res<Match>,
+    names: Query<&Name>,
+    mut events: EventReader<MatchEvent>,
+    mut announce: EventWriter<Announce>,
 ) {
-    for (battle, player) in &battlefields {
-        let Some((mut crowd, _)) = players.iter_mut().find(|(_, p)| *p == player) else {
+    for event in events.read() {
+        let MatchEvent(GameEvent::Abandoned(card)) = event else {
             continue;
         };
-
-        crowd.0 = battle.0.len() as u8;
+        let Some(name) = game.entity(*card).and_then(|card| names.get(card).ok()) else {
+            continue;
+        };
+        announce.send(Announce(format!("{name} left, the crowd is too small")));
     }
 }
@@ -1,35 +1,52 @@
+use std::cmp::Ordering;
+
 use bevy::color::palettes::css::{BLACK, GRAY, WHITE};
-use bevy::color::palettes::tailwind::{GRAY_400, GRAY_700, GRAY_950};
+use bevy::color::palettes::tailwind::{
+    AMBER_200,
+    GRAY_400,
+    GRAY_700,
+    GRAY_950,
+    GREEN_400,
+    RED_400,
+};
 use bevy::sprite::Anchor;
 use bevy::text::Text2dBounds;
 
-use super::deck::GlobalCards;
+use super::card_text::{fit_font_size, fit_line_font_size, text_height};
+use super::deck::{CardDatabase, GlobalCards};
 use super::hovering::Hoverable;
+use super::targeting::ValidTarget;
 use super::{Costs, Hp, Power};
+use crate::card_loader::CardCollection;
 use crate::prelude::*;
-
-#[derive(Clone)]
-pub struct CardGameplayInfo {
-    pub cast_crowd: u8,
-    pub minimum_crowd: u8,
-    pub hp: u8,
-    pub power: u8,
-}
+use crate::rules::cards::{CardGameplayInfo, CardId, CardKind};
 
 #[derive(Clone)]
 pub struct CardInfo {
+    pub id: CardId,
     pub gameplay: CardGameplayInfo,
-    pub name: &'static str,
+    pub name: String,
     pub img: Handle<Image>,
-    pub flavor_text: Option<&'static str>,
+    pub flavor_text: Option<String>,
+    // Generated from the abilities when the cards are loaded
+    pub rules_text: String,
+    // Tokens only exist when an effect creates them
+    pub token: bool,
 }
 
 #[derive(Component)]
 pub struct Card;
 
-#[derive(Component)]
+#[derive(Component, PartialEq, Eq)]
 pub struct ShowFront(pub bool);
 
+// The printed stats of a card, before anything modifies them
+#[derive(Component)]
+pub struct BaseStats {
+    pub power: u8,
+    pub hp: u8,
+}
+
 #[derive(Component)]
 struct Front;
 
@@ -48,14 +65,14 @@ struct CardArt;
 #[derive(Component)]
 pub struct CardGray;
 
-#[derive(Component)]
+#[derive(Component, PartialEq, Eq)]
 pub struct Deck(pub Vec<Entity>);
 
 pub struct CardPlugin;
 
 impl Plugin for CardPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnExit(MainState::Loading), create_global_cards);
+        app.add_systems(OnExit(MainState::Loading), create_card_database);
 
         app.add_systems(
             Update,
@@ -70,8 +87,66 @@ impl Plugin for CardPlugin {
     }
 }
 
-fn create_global_cards(mut commands: Commands, assets: Res<assets::Cards>) {
-    commands.insert_resource(GlobalCards::new(&assets));
+fn create_card_database(
+    mut commands: Commands,
+    assets: Res<assets::Cards>,
+    collections: Res<Assets<CardCollection>>,
+) {
+    let cards = collections
+        .get(&assets.definitions)
+        .map(|collection| collection.0.as_slice())
+        .unwrap_or_default();
+
+    commands.insert_resource(GlobalCards::new(cards));
+    commands.insert_resource(CardDatabase::new(cards));
+}
+
+// Size of the text area below the name, in the units of the scaled down text
+const TEXT_BOX_WIDTH: f32 = 560.0;
+const TEXT_BOX_HEIGHT: f32 = 290.0;
+const MIN_TEXT_SIZE: f32 = 30.0;
+
+struct TextLayout {
+    rules_size: f32,
+    rules_height: f32,
+    flavor_size: f32,
+    flavor_top: f32,
+}
+
+impl TextLayout {
+    // The rules text gets as much room as it needs, the flavor text gets what is left below it
+    fn new(card: &CardInfo) -> Self {
+        let flavor = card.flavor_text.as_deref().unwrap_or_default();
+        let flavor_space =
+            text_height(flavor, TEXT_BOX_WIDTH, MIN_TEXT_SIZE).min(TEXT_BOX_HEIGHT / 3.0);
+
+        let rules_size = fit_font_size(
+            &card.rules_text,
+            Vec2::new(TEXT_BOX_WIDTH, TEXT_BOX_HEIGHT - flavor_space),
+            60.0,
+            MIN_TEXT_SIZE,
+        );
+        let rules_height = text_height(&card.rules_text, TEXT_BOX_WIDTH, rules_size);
+
+        let flavor_top = if rules_height > 0.0 {
+            rules_height + 20.0
+        } else {
+            0.0
+        };
+        let flavor_size = fit_font_size(
+            flavor,
+            Vec2::new(TEXT_BOX_WIDTH, TEXT_BOX_HEIGHT - flavor_top),
+            50.0,
+            MIN_TEXT_SIZE,
+        );
+
+        Self {
+            rules_size,
+            rules_height,
+            flavor_size,
+            flavor_top,
+        }
+    }
 }
 
 #[allow(clippy::too_many_lines)] // Theres a lot of stuff to spawn
@@ -82,26 +157,33 @@ pub fn spawn_card(
     card: &CardInfo,
     trans: Transform,
 ) -> Entity {
-    commands
-        .spawn((
-            Name::from(card.name),
-            Card,
-            ShowFront(false),
-            VisibilityBundle::default(),
-            TransformBundle::from_transform(trans),
-            Hp {
-                current_hp: card.gameplay.hp,
-                max_hp: card.gameplay.hp,
-            },
-            Power(card.gameplay.power),
-            Costs {
-                minimum: card.gameplay.minimum_crowd,
-                cast: card.gameplay.cast_crowd,
-            },
-            Hoverable {
-                size: Vec2::new(52.0 * 5.0, 84.0 * 5.0),
-            },
-        ))
+    let mut entity = commands.spawn((
+        Name::new(card.name.clone()),
+        Card,
+        ShowFront(false),
+        VisibilityBundle::default(),
+        TransformBundle::from_transform(trans),
+        Hp {
+            current_hp: card.gameplay.hp,
+            max_hp: card.gameplay.hp,
+        },
+        Power(card.gameplay.power),
+        BaseStats {
+            power: card.gameplay.power,
+            hp: card.gameplay.hp,
+        },
+        Costs {
+            minimum: card.gameplay.minimum_crowd,
+            cast: card.gameplay.cast_crowd,
+        },
+        Hoverable {
+            size: Vec2::new(52.0 * 5.0, 84.0 * 5.0),
+        },
+    ));
+
+    let layout = TextLayout::new(card);
+
+    entity
         .with_children(|commands| {
             commands.spawn((
                 Name::new("Back"),
@@ -136,9 +218,13 @@ pub fn spawn_card(
                         Name::new("Name"),
                         Text2dBundle {
                             text: Text::from_section(
-                                card.name,
+                                card.name.clone(),
                                 TextStyle {
-                                    font_size: 100.0,
+                                    font_size: fit_line_font_size(
+                                        &card.name,
+                                        TEXT_BOX_WIDTH,
+                                        100.0,
+                                    ),
                                     font: font_assets.pixel.clone_weak(),
                                     color: BLACK.into(),
                                 },
@@ -148,23 +234,27 @@ pub fn spawn_card(
                             ..default()
                         },
                     ));
-                    commands.spawn((
-                        Name::new("Stats"),
-                        CardStatsText,
-                        Text2dBundle {
-                            text: Text::from_section(
-                                "0/0",
-                                TextStyle {
-                                    font: font_assets.pixel.clone_weak(),
-                                    font_size: 90.0,
-                                    color: WHITE.into(),
-                                },
-                            ),
-                            transform: Transform::from_xyz(19.0, -36.0, 1.0)
-                                .with_scale(Vec3::new(0.1, 0.1, 1.0)),
-                            ..default()
-                        },
-                    ));
+                    if card.gameplay.kind == CardKind::Creature {
+                        commands.spawn((
+                            Name::new("Stats"),
+                            CardStatsText,
+                            Text2dBundle {
+                                text: Text::from_sections(["0", "/", "0"].map(|value| {
+                                    TextSection::new(
+                                        value,
+                                        TextStyle {
+                                            font: font_assets.pixel.clone_weak(),
+                                            font_size: 90.0,
+                                            color: WHITE.into(),
+                                        },
+                                    )
+                                })),
+                                transform: Transform::from_xyz(19.0, -36.0, 1.0)
+                                    .with_scale(Vec3::new(0.1, 0.1, 1.0)),
+                                ..default()
+                            },
+                        ));
+                    }
                     commands.spawn((
                         Name::new("Crowd"),
                         CardCostsText,
@@ -182,22 +272,22 @@ pub fn spawn_card(
                             ..default()
                         },
                     ));
-                    if let Some(flavor) = card.flavor_text {
+                    if !card.rules_text.is_empty() {
                         commands.spawn((
-                            Name::new("Flavor"),
+                            Name::new("Rules"),
                             Text2dBundle {
                                 text: Text::from_section(
-                                    flavor,
+                                    card.rules_text.clone(),
                                     TextStyle {
-                                        font_size: 50.0,
+                                        font_size: layout.rules_size,
                                         font: font_assets.pixel.clone_weak(),
-                                        color: GRAY_400.into(),
+                                        color: WHITE.into(),
                                     },
                                 )
                                 .with_justify(JustifyText::Center),
                                 text_anchor: Anchor::TopLeft,
                                 text_2d_bounds: Text2dBounds {
-                                    size: Vec2::new(560.0, 300.0),
+                                    size: Vec2::new(TEXT_BOX_WIDTH, layout.rules_height),
                                 },
                                 transform: Transform::from_xyz(-28.0, -2.0, 1.0)
                                     .with_scale(Vec3::new(0.1, 0.1, 1.0)),
@@ -205,22 +295,67 @@ pub fn spawn_card(
                             },
                         ));
                     }
+                    if let Some(flavor) = &card.flavor_text {
+                        commands.spawn((
+                            Name::new("Flavor"),
+                            Text2dBundle {
+                                text: Text::from_section(
+                                    flavor.clone(),
+                                    TextStyle {
+                                        font_size: layout.flavor_size,
+                                        font: font_assets.pixel.clone_weak(),
+                                        color: GRAY_400.into(),
+                                    },
+                                )
+                                .with_justify(JustifyText::Center),
+                                text_anchor: Anchor::TopLeft,
+                                text_2d_bounds: Text2dBounds {
+                                    size: Vec2::new(
+                                        TEXT_BOX_WIDTH,
+                                        TEXT_BOX_HEIGHT - layout.flavor_top,
+                                    ),
+                                },
+                                transform: Transform::from_xyz(
+                                    -28.0,
+                                    -2.0 - layout.flavor_top * 0.1,
+                                    1.0,
+                                )
+                                .with_scale(Vec3::new(0.1, 0.1, 1.0)),
+                                ..default()
+                            },
+                        ));
+                    }
                 });
         })
         .id()
 }
 
+// Modified values are colored, so buffs and debuffs stand out
+fn stat_color(value: u8, base: u8) -> Color {
+    match value.cmp(&base) {
+        Ordering::Greater => GREEN_400.into(),
+        Ordering::Less => RED_400.into(),
+        Ordering::Equal => WHITE.into(),
+    }
+}
+
 fn update_card_stats(
-    cards: Query<(&Hp, &Power, &Children), (With<Card>, Or<(Changed<Hp>, Changed<Power>)>)>,
+    cards: Query<
+        (&Hp, &Power, &BaseStats, &Children),
+        (With<Card>, Or<(Changed<Hp>, Changed<Power>)>),
+    >,
     fronts: Query<&Children, With<Front>>,
     mut text: Query<&mut Text, With<CardStatsText>>,
 ) {
-    for (hp, power, children) in &cards {
+    for (hp, power, base, children) in &cards {
         'search: for child in children {
             if let Ok(children) = fronts.get(*child) {
                 for child in children {
                     if let Ok(mut text) = text.get_mut(*child) {
-                        text.sections[0].value = format!("{}/{}", power.0, hp.current_hp);
+                        text.sections[0].value = power.0.to_string();
+                        text.sections[0].style.color = stat_color(power.0, base.power);
+                        text.sections[2].value = hp.current_hp.to_string();
+                        text.sections[2].style.color = stat_color(hp.max_hp, base.hp);
                         break 'search;
                     }
                 }
@@ -277,13 +412,19 @@ fn update_shown_side(
 }
 
 fn set_grayscale(
-    cards: Query<(&Children, Option<&CardGray>), With<Card>>,
+    cards: Query<(&Children, Option<&CardGray>, Option<&ValidTarget>), With<Card>>,
     fronts: Query<&Children, With<Front>>,
     arts: Query<(), With<CardArt>>,
     mut sprites: Query<&mut Sprite>,
 ) {
-    for (children, gray) in &cards {
-        let color = if gray.is_some() { GRAY_700 } else { WHITE };
+    for (children, gray, target) in &cards {
+        let color = if target.is_some() {
+            AMBER_200
+        } else if gray.is_some() {
+            GRAY_700
+        } else {
+            WHITE
+        };
 
         for front in children {
             if let Ok(children) = fronts.get(*front) {
@@ -0,0 +1,186 @@
+use bevy::utils::HashMap;
+
+use crate::prelude::*;
+use crate::rules::cards::{
+    Aura,
+    AuraScope,
+    CardGameplayInfo,
+    CardId,
+    CardKind,
+    Duration,
+    Effect,
+    StatModifier,
+    TargetRule,
+    Trigger,
+    TriggeredEffect,
+};
+
+// Measured from the pixel font, no glyph is wider than half the font size
+const GLYPH_WIDTH: f32 = 0.5;
+const LINE_HEIGHT: f32 = 1.0;
+
+// Describes everything a card does, one ability per line
+pub fn rules_text(gameplay: &CardGameplayInfo, names: &HashMap<CardId, String>) -> String {
+    let mut lines = Vec::new();
+
+    if !gameplay.keywords.is_empty() {
+        let keywords = gameplay
+            .keywords
+            .iter()
+            .map(|keyword| keyword.name())
+            .collect::<Vec<_>>();
+        lines.push(keywords.join(", "));
+    }
+    for triggered in &gameplay.triggers {
+        lines.push(describe_trigger(triggered, gameplay.kind, names));
+    }
+    for aura in &gameplay.auras {
+        lines.push(describe_aura(aura));
+    }
+
+    lines.join("\n")
+}
+
+fn describe_trigger(
+    triggered: &TriggeredEffect,
+    kind: CardKind,
+    names: &HashMap<CardId, String>,
+) -> String {
+    let effect = describe_effect(&triggered.effect, names);
+    let prefix = match triggered.trigger {
+        // Playing a spell is all it does, so there is no need to say when
+        Trigger::Play if kind == CardKind::Spell => return format!("{}.", capitalize(&effect)),
+        Trigger::Play => "When played",
+        Trigger::Draw => "When drawn",
+        Trigger::Death => "When this dies",
+        Trigger::StartOfTurn => "At the start of your turn",
+        Trigger::EndOfTurn => "At the end of your turn",
+    };
+    format!("{prefix}, {effect}.")
+}
+
+fn describe_effect(effect: &Effect, names: &HashMap<CardId, String>) -> String {
+    match effect {
+        Effect::DamageOpponent(amount) => format!("deal {amount} damage to the opponent"),
+        Effect::HealController(amount) => format!("heal {amount}"),
+        Effect::DrawCards(1) => "draw a card".to_owned(),
+        Effect::DrawCards(amount) => format!("draw {amount} cards"),
+        Effect::DamageCreature(amount, rule) => {
+            format!("deal {amount} damage to {}", describe_target(*rule))
+        }
+        Effect::ModifyCreature(modifier, rule) => format!(
+            "give {} {} {}",
+            describe_target(*rule),
+            describe_bonus(i16::from(modifier.power), i16::from(modifier.hp)),
+            describe_duration(modifier),
+        ),
+        Effect::SummonToken(id) => {
+            let name = names.get(id).map_or(id.0.as_str(), String::as_str);
+            format!("summon a {name}")
+        }
+    }
+}
+
+const fn describe_target(rule: TargetRule) -> &'static str {
+    match rule {
+        TargetRule::Any => "a creature",
+        TargetRule::Enemy => "an enemy creature",
+        TargetRule::Friendly => "a friendly creature",
+    }
+}
+
+fn describe_bonus(power: i16, hp: i16) -> String {
+    let mut parts = Vec::new();
+    if power != 0 {
+        parts.push(format!("{power:+} power"));
+    }
+    if hp != 0 {
+        parts.push(format!("{hp:+} hp"));
+    }
+    parts.join(" and ")
+}
+
+fn describe_duration(modifier: &StatModifier) -> String {
+    match modifier.duration {
+        Duration::EndOfTurn => "until the end of the turn".to_owned(),
+        Duration::Turns(1) => "for a turn".to_owned(),
+        Duration::Turns(turns) => format!("for {turns} turns"),
+    }
+}
+
+fn describe_aura(aura: &Aura) -> String {
+    let (creatures, cards) = match aura.affects {
+        AuraScope::OtherFriendly => ("Your other creatures", "Your cards"),
+        AuraScope::Enemy => ("Enemy creatures", "Enemy cards"),
+    };
+
+    let mut sentences = Vec::new();
+    if aura.power != 0 || aura.hp != 0 {
+        let bonus = describe_bonus(i16::from(aura.power), i16::from(aura.hp));
+        sentences.push(format!("{creatures} get {bonus}."));
+    }
+    if aura.cast_crowd != 0 {
+        let direction = if aura.cast_crowd > 0 { "more" } else { "less" };
+        let amount = aura.cast_crowd.unsigned_abs();
+        sentences.push(format!("{cards} cost {amount} {direction} crowd."));
+    }
+    sentences.join(" ")
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    chars.next().map_or_else(String::new, |first| {
+        first.to_uppercase().chain(chars).collect()
+    })
+}
+
+// Estimates how many lines the text wraps into, breaking between words
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // Both are small and positive
+fn line_count(text: &str, width: f32, font_size: f32) -> usize {
+    let max_chars = ((width / (font_size * GLYPH_WIDTH)) as usize).max(1);
+
+    text.lines()
+        .map(|paragraph| {
+            let mut lines = 1;
+            let mut current = 0;
+            for word in paragraph.split_whitespace() {
+                let length = word.chars().count();
+                if current > 0 && current + 1 + length <= max_chars {
+                    current += 1 + length;
+                    continue;
+                }
+                if current > 0 {
+                    lines += 1;
+                }
+                // Words longer than a whole line get split over several
+                lines += (length - 1) / max_chars;
+                current = (length - 1) % max_chars + 1;
+            }
+            lines
+        })
+        .sum()
+}
+
+#[allow(clippy::cast_precision_loss)] // Cards never have that many lines
+pub fn text_height(text: &str, width: f32, font_size: f32) -> f32 {
+    if text.is_empty() {
+        return 0.0;
+    }
+    line_count(text, width, font_size) as f32 * font_size * LINE_HEIGHT
+}
+
+// The biggest font size at which the wrapped text still fits into the bounds
+pub fn fit_font_size(text: &str, bounds: Vec2, max_size: f32, min_size: f32) -> f32 {
+    let mut size = max_size;
+    while size > min_size && text_height(text, bounds.x, size) > bounds.y {
+        size -= 2.0;
+    }
+    size.max(min_size)
+}
+
+// The biggest font size at which the text fits on a single line
+#[allow(clippy::cast_precision_loss)] // Names are short
+pub fn fit_line_font_size(text: &str, width: f32, max_size: f32) -> f32 {
+    let length = text.chars().count().max(1) as f32;
+    (width / (length * GLYPH_WIDTH)).min(max_size)
+}
@@ -1,41 +1,58 @@
-use super::card::{CardGameplayInfo, CardInfo};
+use std::sync::Arc;
+
+use bevy::utils::HashMap;
+
+use super::card::CardInfo;
 use crate::prelude::*;
+use crate::rules::cards::{CardId, CardLibrary};
 
 #[derive(Resource)]
-pub struct GlobalCards(pub Vec<CardInfo>);
+pub struct GlobalCards(pub Vec<CardId>);
 
 impl GlobalCards {
-    pub fn new(assets: &assets::Cards) -> Self {
-        let base = vec![
-            CardInfo {
-                name: "Test Unit",
-                img: assets.placeholder.clone_weak(),
-                flavor_text: Some("Beep Boop, debugging is fun"),
-                gameplay: CardGameplayInfo {
-                    cast_crowd: 0,
-                    minimum_crowd: 0,
-                    hp: 2,
-                    power: 2,
-                },
-            },
-            CardInfo {
-                name: "Ghost",
-                img: assets.ghost.clone_weak(),
-                flavor_text: Some("I am very scary :P"),
-                gameplay: CardGameplayInfo {
-                    cast_crowd: 1,
-                    minimum_crowd: 0,
-                    hp: 1,
-                    power: 1,
-                },
-            },
-        ];
-
+    pub fn new(cards: &[CardInfo]) -> Self {
         // FOR TESTING
         let mut deck = Vec::new();
         for _ in 0..10 {
-            deck.extend_from_slice(&base);
+            deck.extend(
+                cards
+                    .iter()
+                    .filter(|card| !card.token)
+                    .map(|card| card.id.clone()),
+            );
         }
         Self(deck)
     }
 }
+
+// Every known card, with its definition and art
+#[derive(Resource)]
+pub struct CardDatabase {
+    cards: HashMap<CardId, CardInfo>,
+    // The same cards as the rules see them, shared with every match
+    library: Arc<CardLibrary>,
+}
+
+impl CardDatabase {
+    pub fn new(cards: &[CardInfo]) -> Self {
+        Self {
+            cards: cards
+                .iter()
+                .map(|card| (card.id.clone(), card.clone()))
+                .collect(),
+            library: Arc::new(CardLibrary::new(
+                cards
+                    .iter()
+                    .map(|card| (card.id.clone(), card.gameplay.clone())),
+            )),
+        }
+    }
+
+    pub fn get(&self, id: &CardId) -> Option<&CardInfo> {
+        self.cards.get(id)
+    }
+
+    pub fn library(&self) -> Arc<CardLibrary> {
+        Arc::clone(&self.library)
+    }
+}
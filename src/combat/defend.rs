@@ -0,0 +1,98 @@
+use super::attack::Attacking;
+use super::hovering::Hovered;
+use super::mirror::{Match, TakeAction};
+use super::{PlayerReference, TurnIcon, TurnState, WhosTurnIsIt};
+use crate::prelude::*;
+use crate::rules::{Action, Side};
+
+pub struct DefendPlugin;
+
+#[derive(Component)]
+pub struct Blocking(pub Entity);
+
+#[derive(Component)]
+pub struct SelectedBlocker;
+
+impl Plugin for DefendPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnExit(TurnState::SelectDefenders), clear_selected_blocker);
+        app.add_systems(
+            Update,
+            (handle_blocker_input, handle_confirm_input)
+                .run_if(in_state(WhosTurnIsIt(PlayerReference::Ai)))
+                .run_if(in_state(TurnState::SelectDefenders)),
+        );
+    }
+}
+
+fn clear_selected_blocker(mut commands: Commands, selected: Query<Entity, With<SelectedBlocker>>) {
+    for card in &selected {
+        commands.entity(card).remove::<SelectedBlocker>();
+    }
+}
+
+fn handle_blocker_input(
+    mut commands: Commands,
+    cards: Query<(Entity, Option<&Attacking>, Option<&Blocking>), With<Hovered>>,
+    game: Res<Match>,
+    selected: Query<Entity, With<SelectedBlocker>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    mut actions: EventWriter<TakeAction>,
+) {
+    if !mouse.just_pressed(MouseButton::Left) {
+        return;
+    }
+
+    let battlefield = &game.state().player(Side::Player).battlefield;
+    for (entity, attacking, blocking) in &cards {
+        let Some(card) = game.card(entity) else {
+            continue;
+        };
+
+        if battlefield.contains(&card) {
+            for previous in &selected {
+                commands.entity(previous).remove::<SelectedBlocker>();
+            }
+
+            if blocking.is_some() {
+                actions.send(TakeAction {
+                    player: PlayerReference::Player,
+                    action: Action::AssignBlocker {
+                        blocker: card,
+                        attacker: None,
+                    },
+                });
+            } else {
+                commands.entity(entity).insert(SelectedBlocker);
+            }
+        } else if attacking.is_some() {
+            let Ok(selected) = selected.get_single() else {
+                continue;
+            };
+            commands.entity(selected).remove::<SelectedBlocker>();
+            let Some(blocker) = game.card(selected) else {
+                continue;
+            };
+            actions.send(TakeAction {
+                player: PlayerReference::Player,
+                action: Action::AssignBlocker {
+                    blocker,
+                    attacker: Some(card),
+                },
+            });
+        }
+    }
+}
+
+fn handle_confirm_input(
+    icon: Query<(), (With<TurnIcon>, With<Hovered>)>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    mut actions: EventWriter<TakeAction>,
+) {
+    if mouse.just_pressed(MouseButton::Left) && !icon.is_empty() {
+        actions.send(TakeAction {
+            player: PlayerReference::Player,
+            action: Action::ConfirmBlockers,
+        });
+    }
+}
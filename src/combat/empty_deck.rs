@@ -0,0 +1,40 @@
+use super::announcement::Announce;
+use super::mirror::MatchEvent;
+use crate::prelude::*;
+use crate::rules::{EmptyDeckResult, GameEvent, Side};
+
+pub struct EmptyDeckPlugin;
+
+impl Plugin for EmptyDeckPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            announce_empty_deck.run_if(in_state(MainState::Combat)),
+        );
+    }
+}
+
+fn announce_empty_deck(mut events: EventReader<MatchEvent>, mut announce: EventWriter<Announce>) {
+    for event in events.read() {
+        let MatchEvent(GameEvent::DeckEmpty { side, result }) = event else {
+            continue;
+        };
+        let who = match side {
+            Side::Player => "Your",
+            Side::Ai => "The opponent's",
+        };
+        let message = match result {
+            EmptyDeckResult::Fatigue(damage) => {
+                format!("{who} deck is empty, fatigue deals {damage} damage")
+            }
+            EmptyDeckResult::Lost => format!("{who} deck is empty, the match is lost"),
+            EmptyDeckResult::Reshuffled(0) => {
+                format!("{who} deck and graveyard are empty, no card is drawn")
+            }
+            EmptyDeckResult::Reshuffled(amount) => {
+                format!("{who} deck is empty, {amount} cards are shuffled back in")
+            }
+        };
+        announce.send(Announce(message));
+    }
+}
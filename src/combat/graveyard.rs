@@ -0,0 +1,52 @@
+use super::card::{Card, ShowFront};
+use super::PlayerReference;
+use crate::position::{AxisAnchor, Relative, RelativeAxis};
+use crate::prelude::*;
+
+pub struct GraveyardPlugin;
+
+#[derive(Component, PartialEq, Eq)]
+pub struct Graveyard(pub Vec<Entity>);
+
+impl Plugin for GraveyardPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            position_cards_in_graveyard.run_if(in_state(MainState::Combat)),
+        );
+    }
+}
+
+#[allow(clippy::cast_precision_loss)] // The graveyard should never be very large
+fn position_cards_in_graveyard(
+    graveyards: Query<(&Graveyard, &PlayerReference), Changed<Graveyard>>,
+    mut cards: Query<(&mut Relative, &mut Transform, &mut ShowFront), With<Card>>,
+) {
+    for (graveyard, player) in &graveyards {
+        let y_level = if *player == PlayerReference::Player {
+            AxisAnchor::Neg
+        } else {
+            AxisAnchor::Pos
+        };
+
+        for (index, card) in graveyard.0.iter().enumerate() {
+            let Ok((mut rel, mut trans, mut front)) = cards.get_mut(*card) else {
+                continue;
+            };
+
+            front.0 = true;
+            trans.scale = Vec3::new(0.5, 0.5, 1.0);
+            trans.translation.z = index as f32 * 10.0;
+            *rel = Relative {
+                x: Some(RelativeAxis {
+                    anchor: AxisAnchor::Neg,
+                    amount: 250.0,
+                }),
+                y: Some(RelativeAxis {
+                    anchor: y_level,
+                    amount: 150.0,
+                }),
+            };
+        }
+    }
+}
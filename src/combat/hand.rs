@@ -1,8 +1,12 @@
-use super::card::{Card, CardGray, Deck, ShowFront};
+use super::announcement::Announce;
+use super::card::{Card, CardGray, ShowFront};
 use super::hovering::Hovered;
-use super::{AllowedToPlay, PlayerReference, TurnState, WhosTurnIsIt};
+use super::mirror::{Match, TakeAction};
+use super::targeting::PendingTarget;
+use super::{AllowedToPlay, PlayerReference, TurnIcon, TurnState, WhosTurnIsIt};
 use crate::position::{AxisAnchor, Relative, RelativeAxis};
 use crate::prelude::*;
+use crate::rules::{Action, Side};
 
 pub struct HandPlugin;
 
@@ -12,76 +16,36 @@ pub struct InHand;
 #[derive(Component)]
 pub struct Focused;
 
-#[derive(Component)]
+#[derive(Component, PartialEq, Eq)]
 pub struct Hand(pub Vec<Entity>);
 
-#[derive(Event)]
-pub struct DrawCard {
-    pub player: PlayerReference,
-    pub amount: usize,
-}
-
-#[derive(Event)]
-pub struct PlayCard {
-    pub player: PlayerReference,
-    pub card: Entity,
-    pub hand_index: usize,
-}
-
 impl Plugin for HandPlugin {
     fn build(&self, app: &mut App) {
-        app.add_event::<DrawCard>();
-        app.add_event::<PlayCard>();
+        app.add_systems(
+            OnEnter(TurnState::Discard),
+            announce_discard.run_if(in_state(WhosTurnIsIt(PlayerReference::Player))),
+        );
         app.add_systems(
             Update,
             (
-                draw_cards,
                 setup_cards_in_hand,
                 position_cards_in_hand,
                 show_allowed_cards,
-                handle_play_input.run_if(
-                    in_state(TurnState::PlayCreature)
+                (handle_play_input, handle_pass_input).run_if(
+                    not(resource_exists::<PendingTarget>)
+                        .and_then(in_state(TurnState::PlayCreature))
+                        .and_then(in_state(WhosTurnIsIt(PlayerReference::Player))),
+                ),
+                handle_discard_input.run_if(
+                    in_state(TurnState::Discard)
                         .and_then(in_state(WhosTurnIsIt(PlayerReference::Player))),
                 ),
-                remove_played_card_from_hand,
             )
                 .run_if(in_state(MainState::Combat)),
         );
     }
 }
 
-fn draw_cards(
-    mut commands: Commands,
-    mut decks: Query<(&mut Deck, &PlayerReference)>,
-    mut events: EventReader<DrawCard>,
-    mut hands: Query<(&mut Hand, &PlayerReference)>,
-) {
-    for event in events.read() {
-        let Some((mut deck, _)) = decks
-            .iter_mut()
-            .find(|(_, player)| event.player == **player)
-        else {
-            continue;
-        };
-        let Some((mut hand, _)) = hands
-            .iter_mut()
-            .find(|(_, player)| event.player == **player)
-        else {
-            continue;
-        };
-
-        for _ in 0..event.amount {
-            let Some(card) = deck.0.pop() else {
-                continue;
-            };
-            hand.0.push(card);
-
-            let mut card = commands.entity(card);
-            card.insert((InHand, event.player));
-        }
-    }
-}
-
 fn setup_cards_in_hand(
     mut query: Query<(&mut ShowFront, &mut Transform, &PlayerReference), Added<InHand>>,
 ) {
@@ -163,51 +127,74 @@ fn show_allowed_cards(
 }
 
 fn handle_play_input(
+    mut commands: Commands,
     cards: Query<Entity, (With<Focused>, With<AllowedToPlay>)>,
-    hands: Query<(&Hand, &PlayerReference)>,
+    game: Res<Match>,
     mouse: Res<ButtonInput<MouseButton>>,
-    mut play: EventWriter<PlayCard>,
+    mut actions: EventWriter<TakeAction>,
 ) {
     if mouse.just_pressed(MouseButton::Left) {
-        let Ok(card) = cards.get_single() else {
+        let Ok(entity) = cards.get_single() else {
             return;
         };
-
-        let Some((player_hand, _)) = hands
-            .iter()
-            .find(|(_, player)| **player == PlayerReference::Player)
+        let Some(card) = game
+            .card(entity)
+            .filter(|card| game.state().player(Side::Player).hand.contains(card))
         else {
             return;
         };
 
-        let Some(index) = player_hand.0.iter().position(|e| *e == card) else {
+        // Cards that need a target are played once the target is picked
+        if let Some(rule) = game.state().play_target(card) {
+            commands.insert_resource(PendingTarget { card, rule });
             return;
-        };
+        }
 
-        play.send(PlayCard {
+        actions.send(TakeAction {
             player: PlayerReference::Player,
-            card,
-            hand_index: index,
+            action: Action::Play { card, target: None },
         });
     }
 }
 
-fn remove_played_card_from_hand(
-    mut commands: Commands,
-    mut hands: Query<(&mut Hand, &PlayerReference)>,
-    mut play: EventReader<PlayCard>,
+fn handle_pass_input(
+    icon: Query<(), (With<TurnIcon>, With<Hovered>)>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    mut actions: EventWriter<TakeAction>,
 ) {
-    for event in play.read() {
-        let Some((mut hand, _)) = hands
-            .iter_mut()
-            .find(|(_, player)| **player == event.player)
-        else {
-            continue;
-        };
+    if mouse.just_pressed(MouseButton::Left) && !icon.is_empty() {
+        actions.send(TakeAction {
+            player: PlayerReference::Player,
+            action: Action::Pass,
+        });
+    }
+}
 
-        hand.0.remove(event.hand_index);
-        commands
-            .entity(event.card)
-            .remove::<(InHand, Focused, AllowedToPlay)>();
+fn announce_discard(game: Res<Match>, mut announce: EventWriter<Announce>) {
+    let hand = &game.state().player(Side::Player).hand;
+    let excess = hand
+        .len()
+        .saturating_sub(game.state().rules().max_hand_size);
+    let cards = if excess == 1 { "card" } else { "cards" };
+    announce.send(Announce(format!(
+        "Your hand is too large, discard {excess} {cards}"
+    )));
+}
+
+fn handle_discard_input(
+    cards: Query<Entity, With<Focused>>,
+    game: Res<Match>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    mut actions: EventWriter<TakeAction>,
+) {
+    if !mouse.just_pressed(MouseButton::Left) {
+        return;
+    }
+
+    for card in cards.iter().filter_map(|entity| game.card(entity)) {
+        actions.send(TakeAction {
+            player: PlayerReference::Player,
+            action: Action::Discard(card),
+        });
     }
 }
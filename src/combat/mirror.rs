@@ -0,0 +1,293 @@
+use super::attack::Attacking;
+use super::battlefield::BattleField;
+use super::card::{spawn_card, CardGray, Deck, ShowFront};
+use super::deck::CardDatabase;
+use super::defend::Blocking;
+use super::graveyard::Graveyard;
+use super::hand::{Focused, Hand, InHand};
+use super::tokens::Token;
+use super::{
+    AllowedToPlay,
+    Controller,
+    Costs,
+    Crowd,
+    Hp,
+    PlayerReference,
+    Power,
+    TurnState,
+    WhosTurnIsIt,
+};
+use crate::position::Relative;
+use crate::prelude::*;
+use crate::rng::GameRng;
+use crate::rules::{Action, CardRef, GameEvent, GameState, Phase, RuleSet};
+
+pub struct MirrorPlugin;
+
+// The match as the rules see it, everything on screen during combat shows this
+#[derive(Resource)]
+pub struct Match {
+    state: GameState,
+    // The entity showing each card, in the same order as the cards of the state
+    cards: Vec<Entity>,
+}
+
+impl Match {
+    pub const fn new(state: GameState) -> Self {
+        Self {
+            state,
+            cards: Vec::new(),
+        }
+    }
+
+    pub const fn state(&self) -> &GameState {
+        &self.state
+    }
+
+    pub fn entity(&self, card: CardRef) -> Option<Entity> {
+        self.cards.get(card.0).copied()
+    }
+
+    pub fn entities(&self, cards: &[CardRef]) -> Vec<Entity> {
+        cards.iter().filter_map(|card| self.entity(*card)).collect()
+    }
+
+    pub fn card(&self, entity: Entity) -> Option<CardRef> {
+        self.cards
+            .iter()
+            .position(|card| *card == entity)
+            .map(CardRef)
+    }
+}
+
+#[derive(Resource)]
+pub struct MatchRules(pub RuleSet);
+
+// Both players act through this, whether it is allowed is up to the rules
+#[derive(Event)]
+pub struct TakeAction {
+    pub player: PlayerReference,
+    pub action: Action,
+}
+
+#[derive(Event)]
+pub struct MatchEvent(pub GameEvent);
+
+impl Plugin for MirrorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<TakeAction>();
+        app.add_event::<MatchEvent>();
+        app.add_systems(
+            Update,
+            (
+                apply_actions,
+                (
+                    spawn_card_entities,
+                    mirror_zones,
+                    mirror_cards,
+                    mirror_players,
+                    mirror_turn,
+                )
+                    .chain()
+                    .run_if(resource_exists_and_changed::<Match>),
+            )
+                .chain()
+                .run_if(in_state(MainState::Combat)),
+        );
+    }
+}
+
+pub fn apply_actions(
+    mut actions: EventReader<TakeAction>,
+    mut game: ResMut<Match>,
+    mut rng: ResMut<GameRng>,
+    mut events: EventWriter<MatchEvent>,
+) {
+    for TakeAction { player, action } in actions.read() {
+        if game.state.decider() != Some((*player).into()) {
+            continue;
+        }
+        // Clicking something that can't be done right now is fine, it just does nothing
+        if let Err(error) = game.state.apply(*action, &mut rng.0) {
+            debug!("{error}");
+        }
+    }
+
+    // Setting up the match can cause events too, like running out of cards
+    if !game.state.events().is_empty() {
+        events.send_batch(game.state.take_events().into_iter().map(MatchEvent));
+    }
+}
+
+// Cards get an entity the first time they show up, tokens in the middle of the match
+fn spawn_card_entities(
+    mut commands: Commands,
+    mut game: ResMut<Match>,
+    card_assets: Res<assets::Cards>,
+    fonts: Res<assets::Fonts>,
+    database: Res<CardDatabase>,
+) {
+    let game = &mut *game;
+    for index in game.cards.len()..game.state.cards().len() {
+        let card = game.state.card(CardRef(index));
+        let Some(info) = database.get(&card.rules.id) else {
+            warn!("No card info for {:?}", card.rules.id);
+            game.cards.push(Entity::PLACEHOLDER);
+            continue;
+        };
+
+        let entity = spawn_card(
+            &mut commands,
+            &card_assets,
+            &fonts,
+            info,
+            Transform::from_scale(Vec3::new(0.5, 0.5, 1.0)),
+        );
+        commands.entity(entity).insert((
+            PlayerReference::from(card.owner),
+            StateScoped(MainState::Combat),
+            // Filled in by the layout of whatever zone the card is in
+            Relative { x: None, y: None },
+        ));
+        if card.token {
+            commands.entity(entity).insert((Token, ShowFront(true)));
+        }
+        game.cards.push(entity);
+    }
+}
+
+fn mirror_zones(
+    game: Res<Match>,
+    mut zones: Query<(
+        AnyOf<(&mut Deck, &mut Hand, &mut BattleField, &mut Graveyard)>,
+        &PlayerReference,
+    )>,
+) {
+    for ((deck, hand, battlefield, graveyard), player) in &mut zones {
+        let player = game.state.player((*player).into());
+        if let Some(mut deck) = deck {
+            deck.set_if_neq(Deck(game.entities(&player.deck)));
+        }
+        if let Some(mut hand) = hand {
+            hand.set_if_neq(Hand(game.entities(&player.hand)));
+        }
+        if let Some(mut battlefield) = battlefield {
+            battlefield.set_if_neq(BattleField(game.entities(&player.battlefield)));
+        }
+        if let Some(mut graveyard) = graveyard {
+            graveyard.set_if_neq(Graveyard(game.entities(&player.graveyard)));
+        }
+    }
+}
+
+fn mirror_cards(
+    mut commands: Commands,
+    game: Res<Match>,
+    mut cards: Query<(
+        &mut Power,
+        &mut Hp,
+        &mut Costs,
+        &mut ShowFront,
+        Has<InHand>,
+        Has<Attacking>,
+        Option<&Blocking>,
+    )>,
+) {
+    let state = &game.state;
+    for (index, entity) in game.cards.iter().enumerate() {
+        let card = CardRef(index);
+        let Ok((mut power, mut hp, mut costs, mut front, in_hand, attacking, blocking)) =
+            cards.get_mut(*entity)
+        else {
+            continue;
+        };
+
+        power.set_if_neq(Power(state.power(card)));
+        hp.set_if_neq(Hp {
+            max_hp: state.max_hp(card),
+            current_hp: state.hp(card),
+        });
+        costs.set_if_neq(Costs {
+            cast: state.cast_cost(card),
+            minimum: state.card(card).gameplay().minimum_crowd,
+        });
+
+        let mut entity = commands.entity(*entity);
+        if state.in_hand(card) {
+            if !in_hand {
+                entity.insert(InHand);
+            }
+            if state.is_playable(card) {
+                entity.insert(AllowedToPlay);
+            } else {
+                entity.remove::<AllowedToPlay>();
+            }
+        } else if in_hand {
+            entity.remove::<(InHand, Focused, AllowedToPlay, CardGray)>();
+        }
+
+        if state.on_battlefield(card) {
+            front.set_if_neq(ShowFront(true));
+        }
+
+        let should_attack = state.attackers().contains(&card);
+        if should_attack && !attacking {
+            entity.insert(Attacking);
+        } else if !should_attack && attacking {
+            entity.remove::<Attacking>();
+        }
+
+        let blocked = state
+            .blocks()
+            .iter()
+            .find(|(blocker, _)| *blocker == card)
+            .and_then(|(_, attacker)| game.entity(*attacker));
+        match (blocked, blocking) {
+            (Some(attacker), Some(blocking)) if blocking.0 == attacker => {}
+            (Some(attacker), _) => {
+                entity.insert(Blocking(attacker));
+            }
+            (None, Some(_)) => {
+                entity.remove::<Blocking>();
+            }
+            (None, None) => {}
+        }
+    }
+}
+
+fn mirror_players(
+    game: Res<Match>,
+    mut controllers: Query<(&mut Hp, &mut Crowd, &PlayerReference), With<Controller>>,
+) {
+    for (mut hp, mut crowd, player) in &mut controllers {
+        let player = game.state.player((*player).into());
+        hp.set_if_neq(Hp {
+            max_hp: player.max_hp,
+            current_hp: player.hp,
+        });
+        crowd.set_if_neq(Crowd(player.crowd()));
+    }
+}
+
+fn mirror_turn(
+    game: Res<Match>,
+    turn: Res<State<TurnState>>,
+    mut next_turn: ResMut<NextState<TurnState>>,
+    player: Res<State<WhosTurnIsIt>>,
+    mut next_player: ResMut<NextState<WhosTurnIsIt>>,
+) {
+    let phase = match game.state.phase() {
+        Phase::PlayCreature => TurnState::PlayCreature,
+        Phase::SelectAttackers => TurnState::SelectAttackers,
+        Phase::SelectDefenders => TurnState::SelectDefenders,
+        Phase::Discard => TurnState::Discard,
+    };
+    if *turn.get() != phase {
+        next_turn.set(phase);
+    }
+
+    let current = WhosTurnIsIt(game.state.current().into());
+    if *player.get() != current {
+        next_player.set(current);
+    }
+}
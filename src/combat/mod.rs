@@ -1,49 +1,69 @@
+use ai::{AiProfile, AiProfiles};
 use battlefield::BattleField;
 use bevy::color::palettes::tailwind::BLUE_300;
 use bevy::ecs::query::{QueryData, QueryFilter, WorldQuery};
-use card::{spawn_card, CardInfo, Deck};
-use deck::GlobalCards;
-use hand::{DrawCard, Hand, InHand, PlayCard};
+use card::{Deck, ShowFront};
+use deck::{CardDatabase, GlobalCards};
+use graveyard::Graveyard;
+use hand::Hand;
+use hovering::Hoverable;
+use mirror::{Match, MatchRules};
 
 use crate::data::PlayerInfo;
+use crate::encounter_loader::Encounter;
 use crate::position::{AxisAnchor, Relative, RelativeAxis};
 use crate::prelude::*;
+use crate::rng::{self, GameRng};
+use crate::rules::cards::CardId;
+use crate::rules::{GameState, PlayerSetup, Side};
 
-mod ai;
+pub mod ai;
+mod announcement;
+mod attack;
 mod battlefield;
 pub mod card;
+pub mod card_text;
 mod deck;
+mod defend;
+mod empty_deck;
+mod graveyard;
 mod hand;
 mod healthbar;
 mod hovering;
+mod mirror;
+pub mod outcome;
+mod search;
+mod targeting;
+mod tokens;
 
 pub struct CombatPlugin;
 
 #[derive(Resource)]
 pub struct OpponentInfo {
     pub hp: u8,
-    pub deck: Vec<CardInfo>,
+    pub deck: Vec<CardId>,
+    pub ai: AiProfile,
 }
 
 #[derive(Component)]
 struct Controller;
 
-#[derive(Component)]
+#[derive(Component, PartialEq, Eq)]
 struct Hp {
     max_hp: u8,
     current_hp: u8,
 }
 
-#[derive(Component)]
+#[derive(Component, PartialEq, Eq)]
 struct Costs {
     cast: u8,
     minimum: u8,
 }
 
-#[derive(Component)]
+#[derive(Component, PartialEq, Eq)]
 struct Power(u8);
 
-#[derive(Component)]
+#[derive(Component, PartialEq, Eq)]
 struct Crowd(u8);
 
 #[derive(Component)]
@@ -62,17 +82,33 @@ enum PlayerReference {
     Ai,
 }
 
+impl From<Side> for PlayerReference {
+    fn from(side: Side) -> Self {
+        match side {
+            Side::Player => Self::Player,
+            Side::Ai => Self::Ai,
+        }
+    }
+}
+
+impl From<PlayerReference> for Side {
+    fn from(player: PlayerReference) -> Self {
+        match player {
+            PlayerReference::Player => Self::Player,
+            PlayerReference::Ai => Self::Ai,
+        }
+    }
+}
+
+// Mirrors the phase of the rules, the rest of a turn happens in between
 #[derive(SubStates, Default, Clone, Hash, Eq, PartialEq, Debug)]
 #[source(MainState = MainState::Combat)]
 pub enum TurnState {
     #[default]
-    DrawCard,
     PlayCreature,
     SelectAttackers,
     SelectDefenders,
-    ResolveCombat,
-    Heal,
-    EndOfTurn,
+    Discard,
 }
 
 #[derive(SubStates, Default, Clone, Hash, Eq, PartialEq, Debug)]
@@ -88,27 +124,35 @@ impl Plugin for CombatPlugin {
             hovering::HoveringPlugin,
             battlefield::BattlePlugin,
             ai::AiPlugin,
+            attack::AttackPlugin,
+            defend::DefendPlugin,
+            graveyard::GraveyardPlugin,
+            announcement::AnnouncementPlugin,
+            outcome::OutcomePlugin,
+            empty_deck::EmptyDeckPlugin,
+            targeting::TargetingPlugin,
+            tokens::TokenPlugin,
+            mirror::MirrorPlugin,
         ));
 
         app.add_sub_state::<TurnState>();
         app.add_sub_state::<WhosTurnIsIt>();
 
         app.add_systems(OnExit(MainState::TestingSetup), create_test_combat);
-        app.add_systems(OnEnter(MainState::Combat), setup_combat);
+        app.add_systems(
+            OnEnter(MainState::Combat),
+            (setup_combat, start_match.after(rng::seed_rng)),
+        );
 
         app.add_systems(
             Update,
             (
-                set_allowed_cards,
                 update_crowd_text,
-                progress_turn_after_play.run_if(in_state(TurnState::PlayCreature)),
+                position_cards_in_deck,
                 update_turn_icon_display.run_if(state_changed::<TurnState>),
             )
                 .run_if(in_state(MainState::Combat)),
         );
-
-        app.add_systems(OnEnter(TurnState::DrawCard), do_draw_card);
-        app.add_systems(OnEnter(TurnState::EndOfTurn), do_end_of_turn);
     }
 }
 
@@ -116,43 +160,53 @@ fn create_test_combat(
     mut commands: Commands,
     mut player: ResMut<PlayerInfo>,
     cards: Res<GlobalCards>,
+    profiles: Res<AiProfiles>,
+    encounters: Res<assets::Encounters>,
+    encounter_assets: Res<Assets<Encounter>>,
 ) {
+    let encounter = encounter_assets.get(&encounters.test);
+    let rules = encounter
+        .map(|encounter| encounter.rules)
+        .unwrap_or_default();
+    commands.insert_resource(MatchRules(rules));
+
+    let ai = encounter
+        .and_then(|encounter| {
+            let profile = profiles.0.get(&encounter.opponent).cloned();
+            if profile.is_none() {
+                warn!(
+                    "Unknown ai profile {:?}, using the default one",
+                    encounter.opponent
+                );
+            }
+            profile
+        })
+        .unwrap_or_default();
+
     commands.insert_resource(OpponentInfo {
         hp: 20,
         deck: cards.0.clone(),
+        ai,
     });
     player.deck.clone_from(&cards.0);
 }
 
-fn spawn_deck(
-    commands: &mut Commands,
-    card_assets: &assets::Cards,
-    font_assets: &assets::Fonts,
-    deck: &[CardInfo],
-    y_level: AxisAnchor,
-) -> Deck {
-    let mut entities = Vec::with_capacity(deck.len());
-    for card in deck {
-        let card = spawn_card(
-            commands,
-            card_assets,
-            font_assets,
-            card,
-            Transform::from_scale(Vec3::new(0.5, 0.5, 1.0)),
-        );
-        commands.entity(card).insert(Relative {
-            x: Some(RelativeAxis {
-                anchor: AxisAnchor::Neg,
-                amount: 100.0,
-            }),
-            y: Some(RelativeAxis {
-                anchor: y_level,
-                amount: 150.0,
-            }),
-        });
-        entities.push(card);
+fn deck_position(player: PlayerReference) -> Relative {
+    let y_level = if player == PlayerReference::Player {
+        AxisAnchor::Neg
+    } else {
+        AxisAnchor::Pos
+    };
+    Relative {
+        x: Some(RelativeAxis {
+            anchor: AxisAnchor::Neg,
+            amount: 100.0,
+        }),
+        y: Some(RelativeAxis {
+            anchor: y_level,
+            amount: 150.0,
+        }),
     }
-    Deck(entities)
 }
 
 fn setup_combat(
@@ -160,25 +214,23 @@ fn setup_combat(
     opponent_info: Res<OpponentInfo>,
     player_info: Res<PlayerInfo>,
     fonts: Res<assets::Fonts>,
-    card_assets: Res<assets::Cards>,
     icon_assets: Res<assets::Icons>,
-    draw_event: EventWriter<DrawCard>,
 ) {
     let (player, ai) = spawn_player_info_objects(&mut commands, &player_info, &opponent_info);
 
-    commands.spawn(healthbar::HealthBarBundle::new(player, false, &fonts));
-    commands.spawn(healthbar::HealthBarBundle::new(ai, true, &fonts));
-
-    spawn_decks(
-        &mut commands,
-        card_assets,
-        &fonts,
-        player_info,
-        opponent_info,
-    );
+    commands.spawn((
+        healthbar::HealthBarBundle::new(player, false, &fonts),
+        StateScoped(MainState::Combat),
+    ));
+    commands.spawn((
+        healthbar::HealthBarBundle::new(ai, true, &fonts),
+        StateScoped(MainState::Combat),
+    ));
 
-    setup_hands(&mut commands, draw_event);
+    setup_decks(&mut commands);
+    setup_hands(&mut commands);
     setup_battlefield(&mut commands);
+    setup_graveyards(&mut commands);
 
     spawn_crowd_text(&mut commands, player, &fonts, false);
     spawn_crowd_text(&mut commands, ai, &fonts, true);
@@ -186,9 +238,52 @@ fn setup_combat(
     spawn_turn_icons(commands, icon_assets);
 }
 
+// The rules deal out the cards, the entities for them are spawned once the match is mirrored
+fn start_match(
+    mut commands: Commands,
+    database: Res<CardDatabase>,
+    player_info: Res<PlayerInfo>,
+    opponent_info: Res<OpponentInfo>,
+    rules: Res<MatchRules>,
+    mut rng: ResMut<GameRng>,
+) {
+    let known_cards = |deck: &[CardId]| {
+        deck.iter()
+            .filter(|id| {
+                let known = database.get(id).is_some();
+                if !known {
+                    warn!("Deck contains unknown card {id:?}");
+                }
+                known
+            })
+            .cloned()
+            .collect()
+    };
+
+    let state = GameState::new(
+        database.library(),
+        [
+            PlayerSetup {
+                hp: player_info.current_hp,
+                max_hp: player_info.max_hp,
+                deck: known_cards(&player_info.deck),
+            },
+            PlayerSetup {
+                hp: opponent_info.hp,
+                max_hp: opponent_info.hp,
+                deck: known_cards(&opponent_info.deck),
+            },
+        ],
+        rules.0,
+        &mut rng.0,
+    );
+    commands.insert_resource(Match::new(state));
+}
+
 fn spawn_turn_icons(mut commands: Commands, icon_assets: Res<assets::Icons>) {
     commands.spawn((
         TurnIcon,
+        StateScoped(MainState::Combat),
         SpriteBundle {
             texture: icon_assets.turn_icons.clone_weak(),
             transform: Transform::from_scale(Vec3::new(7.0, 7.0, 1.0)),
@@ -198,6 +293,9 @@ fn spawn_turn_icons(mut commands: Commands, icon_assets: Res<assets::Icons>) {
             layout: icon_assets.turn_layout.clone_weak(),
             index: 0,
         },
+        Hoverable {
+            size: Vec2::new(32.0, 32.0),
+        },
         Relative {
             x: Some(RelativeAxis {
                 anchor: AxisAnchor::Pos,
@@ -215,60 +313,60 @@ fn setup_battlefield(commands: &mut Commands) {
     commands.spawn((
         BattleField(Vec::new()),
         Name::new("Battle Player"),
+        StateScoped(MainState::Combat),
         PlayerReference::Player,
     ));
     commands.spawn((
         BattleField(Vec::new()),
         Name::new("Battle Ai"),
+        StateScoped(MainState::Combat),
         PlayerReference::Ai,
     ));
 }
 
-fn setup_hands(commands: &mut Commands, mut draw_event: EventWriter<DrawCard>) {
+fn setup_graveyards(commands: &mut Commands) {
     commands.spawn((
-        Name::new("Player hand"),
-        Hand(Vec::new()),
+        Graveyard(Vec::new()),
+        Name::new("Graveyard Player"),
+        StateScoped(MainState::Combat),
         PlayerReference::Player,
     ));
-    commands.spawn((Name::new("Ai hand"), Hand(Vec::new()), PlayerReference::Ai));
-    draw_event.send(DrawCard {
-        player: PlayerReference::Player,
-        amount: 7,
-    });
-    draw_event.send(DrawCard {
-        player: PlayerReference::Ai,
-        amount: 7,
-    });
+    commands.spawn((
+        Graveyard(Vec::new()),
+        Name::new("Graveyard Ai"),
+        StateScoped(MainState::Combat),
+        PlayerReference::Ai,
+    ));
 }
 
-fn spawn_decks(
-    commands: &mut Commands,
-    card_assets: Res<assets::Cards>,
-    fonts: &Res<assets::Fonts>,
-    player_info: Res<PlayerInfo>,
-    opponent_info: Res<OpponentInfo>,
-) {
-    let player_deck = spawn_deck(
-        commands,
-        &card_assets,
-        fonts,
-        &player_info.deck,
-        AxisAnchor::Neg,
-    );
+fn setup_decks(commands: &mut Commands) {
     commands.spawn((
-        player_deck,
+        Deck(Vec::new()),
         Name::new("Player Deck"),
+        StateScoped(MainState::Combat),
         PlayerReference::Player,
     ));
+    commands.spawn((
+        Deck(Vec::new()),
+        Name::new("Ai Deck"),
+        StateScoped(MainState::Combat),
+        PlayerReference::Ai,
+    ));
+}
 
-    let ai_deck = spawn_deck(
-        commands,
-        &card_assets,
-        fonts,
-        &opponent_info.deck,
-        AxisAnchor::Pos,
-    );
-    commands.spawn((ai_deck, Name::new("Ai Deck"), PlayerReference::Ai));
+fn setup_hands(commands: &mut Commands) {
+    commands.spawn((
+        Name::new("Player hand"),
+        StateScoped(MainState::Combat),
+        Hand(Vec::new()),
+        PlayerReference::Player,
+    ));
+    commands.spawn((
+        Name::new("Ai hand"),
+        StateScoped(MainState::Combat),
+        Hand(Vec::new()),
+        PlayerReference::Ai,
+    ));
 }
 
 fn spawn_player_info_objects(
@@ -279,6 +377,7 @@ fn spawn_player_info_objects(
     let player = commands
         .spawn((
             Name::new("Player"),
+            StateScoped(MainState::Combat),
             Controller,
             PlayerReference::Player,
             Hp {
@@ -291,6 +390,7 @@ fn spawn_player_info_objects(
     let ai = commands
         .spawn((
             Name::new("Ai"),
+            StateScoped(MainState::Combat),
             Controller,
             PlayerReference::Ai,
             Hp {
@@ -321,6 +421,7 @@ fn spawn_crowd_text(
     }
     commands.spawn((
         CrowdText(player),
+        StateScoped(MainState::Combat),
         TextBundle {
             text: Text::from_section(
                 "0",
@@ -336,24 +437,6 @@ fn spawn_crowd_text(
     ));
 }
 
-fn set_allowed_cards(
-    mut commands: Commands,
-    query: Query<(Entity, &Costs, &PlayerReference), With<InHand>>,
-    players: Query<(&Crowd, &PlayerReference)>,
-) {
-    for (card, costs, player) in &query {
-        let Some((crowd, _)) = players.iter().find(|(_, p)| *p == player) else {
-            continue;
-        };
-
-        if costs.cast <= crowd.0 {
-            commands.entity(card).insert(AllowedToPlay);
-        } else {
-            commands.entity(card).remove::<AllowedToPlay>();
-        }
-    }
-}
-
 fn update_crowd_text(
     mut texts: Query<(&CrowdText, &mut Text)>,
     query: Query<&Crowd, Changed<Crowd>>,
@@ -366,43 +449,23 @@ fn update_crowd_text(
     }
 }
 
-fn do_draw_card(
-    mut draw_event: EventWriter<DrawCard>,
-    player: Res<State<WhosTurnIsIt>>,
-    mut turn: ResMut<NextState<TurnState>>,
-) {
-    draw_event.send(DrawCard {
-        player: player.0,
-        amount: 1,
-    });
-    turn.set(TurnState::PlayCreature);
-}
-
-fn progress_turn_after_play(
-    mut play: EventReader<PlayCard>,
-    mut turn: ResMut<NextState<TurnState>>,
+fn position_cards_in_deck(
+    decks: Query<(&Deck, &PlayerReference), Changed<Deck>>,
+    mut cards: Query<(&mut Relative, &mut Transform, &mut ShowFront)>,
 ) {
-    if !play.is_empty() {
-        turn.set(TurnState::SelectAttackers);
-        play.clear();
+    for (deck, player) in &decks {
+        for card in &deck.0 {
+            let Ok((mut rel, mut trans, mut front)) = cards.get_mut(*card) else {
+                continue;
+            };
+            front.set_if_neq(ShowFront(false));
+            trans.scale = Vec3::new(0.5, 0.5, 1.0);
+            trans.translation.z = 0.0;
+            *rel = deck_position(*player);
+        }
     }
 }
 
-fn do_end_of_turn(
-    mut turn: ResMut<NextState<TurnState>>,
-    mut new_player: ResMut<NextState<WhosTurnIsIt>>,
-    current_player: Res<State<WhosTurnIsIt>>,
-) {
-    turn.set(TurnState::DrawCard);
-    new_player.set(WhosTurnIsIt(
-        if current_player.0 == PlayerReference::Player {
-            PlayerReference::Ai
-        } else {
-            PlayerReference::Player
-        },
-    ));
-}
-
 fn update_turn_icon_display(
     mut query: Query<&mut TextureAtlas, With<TurnIcon>>,
     turn_state: Res<State<TurnState>>,
@@ -412,8 +475,7 @@ fn update_turn_icon_display(
             TurnState::PlayCreature => 0,
             TurnState::SelectAttackers => 1,
             TurnState::SelectDefenders => 2,
-            TurnState::ResolveCombat => 3,
-            _ => atlas.index,
+            TurnState::Discard => atlas.index,
         }
     }
 }
@@ -0,0 +1,46 @@
+use super::mirror::Match;
+use crate::data::PlayerInfo;
+use crate::prelude::*;
+use crate::rules::Side;
+
+pub struct OutcomePlugin;
+
+#[derive(Resource)]
+pub struct MatchResult {
+    pub player_won: bool,
+    pub starting_hp: u8,
+}
+
+impl Plugin for OutcomePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            end_match
+                .run_if(resource_exists_and_changed::<Match>)
+                .run_if(in_state(MainState::Combat)),
+        );
+    }
+}
+
+fn end_match(
+    mut commands: Commands,
+    game: Res<Match>,
+    mut player_info: ResMut<PlayerInfo>,
+    mut state: ResMut<NextState<MainState>>,
+) {
+    let Some(winner) = game.state().winner() else {
+        return;
+    };
+
+    let player = game.state().player(Side::Player);
+    let player_won = winner == Side::Player;
+
+    let starting_hp = player_info.current_hp;
+    player_info.current_hp = player.hp;
+
+    commands.insert_resource(MatchResult {
+        player_won,
+        starting_hp,
+    });
+    state.set(MainState::GameOver);
+}
@@ -0,0 +1,394 @@
+use bevy::utils::{Duration, Instant};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::Deserialize;
+
+use crate::rules::cards::Keyword;
+use crate::rules::{Action, CardRef, GameState, Phase, Side};
+
+// How long the search may think about a single decision. How many playouts fit in a
+// time budget depends on the machine, so only Iterations replays a seeded match exactly
+#[derive(Clone, Copy, Debug, Deserialize)]
+pub enum SearchBudget {
+    Iterations(u32),
+    TimeMs(u64),
+}
+
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(default)]
+pub struct SearchSettings {
+    pub budget: SearchBudget,
+    // Higher values spend more time on actions that looked bad so far
+    pub exploration: f64,
+    // Playouts stop after this many actions and the match is judged as it stands
+    pub playout_depth: u32,
+}
+
+impl Default for SearchSettings {
+    fn default() -> Self {
+        Self {
+            budget: SearchBudget::Iterations(1000),
+            exploration: std::f64::consts::SQRT_2,
+            playout_depth: 40,
+        }
+    }
+}
+
+// Plays the match out many times, each from a new guess at the cards the side can't see.
+// Both sides follow the policy during playouts.
+pub fn search(
+    state: &GameState,
+    side: Side,
+    settings: &SearchSettings,
+    policy: &impl Fn(&GameState, Side) -> Vec<Action>,
+    rng: &mut impl Rng,
+) -> Option<Action> {
+    let actions = search_actions(state);
+    if actions.len() <= 1 {
+        return actions.first().copied();
+    }
+
+    let mut tree = Tree::new(side);
+    match settings.budget {
+        SearchBudget::Iterations(iterations) => {
+            for _ in 0..iterations {
+                tree.iterate(state.determinize(side, rng), settings, policy, rng);
+            }
+        }
+        SearchBudget::TimeMs(time) => {
+            let deadline = Instant::now() + Duration::from_millis(time);
+            while Instant::now() < deadline {
+                tree.iterate(state.determinize(side, rng), settings, policy, rng);
+            }
+        }
+    }
+    tree.most_visited().or_else(|| actions.first().copied())
+}
+
+// Attackers and blockers are only picked in a fixed order, since only the result matters
+fn search_actions(state: &GameState) -> Vec<Action> {
+    let mut actions = state.legal_actions();
+    match state.phase() {
+        Phase::SelectAttackers => {
+            let last = state.attackers().iter().map(|card| card.0).max();
+            actions.retain(|action| match action {
+                Action::ToggleAttacker(card) => last.is_none_or(|last| card.0 > last),
+                _ => true,
+            });
+        }
+        Phase::SelectDefenders => {
+            let blocks = state.blocks();
+            let last = blocks
+                .iter()
+                .map(|(_, attacker)| block_order(state, *attacker))
+                .max();
+            actions.retain(|action| match action {
+                Action::AssignBlocker {
+                    blocker,
+                    attacker: Some(attacker),
+                } => {
+                    !blocks
+                        .iter()
+                        .any(|(other, blocked)| other == blocker || blocked == attacker)
+                        && last.is_none_or(|last| block_order(state, *attacker) > last)
+                }
+                Action::AssignBlocker { attacker: None, .. } => false,
+                _ => true,
+            });
+        }
+        Phase::PlayCreature | Phase::Discard => {}
+    }
+    actions
+}
+
+fn block_order(state: &GameState, attacker: CardRef) -> (bool, usize) {
+    (!state.card(attacker).has(Keyword::Provoke), attacker.0)
+}
+
+// How good the match is for the side, from 0 for a loss to 1 for a win
+fn evaluate(state: &GameState, side: Side) -> f64 {
+    if let Some(winner) = state.winner() {
+        return if winner == side { 1.0 } else { 0.0 };
+    }
+
+    let health = |side: Side| {
+        let player = state.player(side);
+        f64::from(player.hp) / f64::from(player.max_hp.max(1))
+    };
+    let board = |side: Side| {
+        state
+            .player(side)
+            .battlefield
+            .iter()
+            .map(|card| u32::from(state.power(*card)) + u32::from(state.hp(*card)))
+            .sum::<u32>()
+    };
+
+    let own_board = f64::from(board(side));
+    let enemy_board = f64::from(board(side.other()));
+    let board_share = (own_board - enemy_board) / (own_board + enemy_board + 1.0);
+    0.5 + 0.3 * (health(side) - health(side.other())) + 0.2 * board_share
+}
+
+fn playout(
+    state: &mut GameState,
+    depth: u32,
+    policy: &impl Fn(&GameState, Side) -> Vec<Action>,
+    rng: &mut impl Rng,
+) {
+    let mut steps = 0;
+    while steps < depth {
+        let Some(side) = state.decider() else {
+            break;
+        };
+        let actions = policy(state, side);
+        if actions.is_empty() {
+            break;
+        }
+        for action in actions {
+            steps += 1;
+            if state.apply(action, rng).is_err() {
+                return;
+            }
+        }
+    }
+}
+
+struct Node {
+    action: Option<Action>,
+    actor: Side,
+    children: Vec<usize>,
+    visits: u32,
+    // How often the action was possible when its parent was visited
+    available: u32,
+    reward: f64,
+}
+
+impl Node {
+    const fn new(action: Option<Action>, actor: Side) -> Self {
+        Self {
+            action,
+            actor,
+            children: Vec::new(),
+            visits: 0,
+            available: 0,
+            reward: 0.0,
+        }
+    }
+}
+
+struct Tree {
+    nodes: Vec<Node>,
+}
+
+impl Tree {
+    fn new(side: Side) -> Self {
+        Self {
+            nodes: vec![Node::new(None, side)],
+        }
+    }
+
+    fn iterate(
+        &mut self,
+        mut state: GameState,
+        settings: &SearchSettings,
+        policy: &impl Fn(&GameState, Side) -> Vec<Action>,
+        rng: &mut impl Rng,
+    ) {
+        let mut path = vec![0];
+
+        let mut node = 0;
+        while let Some(actor) = state.decider() {
+            let actions = search_actions(&state);
+            let children = &self.nodes[node].children;
+            let untried = actions
+                .iter()
+                .filter(|action| {
+                    !children
+                        .iter()
+                        .any(|child| self.nodes[*child].action == Some(**action))
+                })
+                .copied()
+                .collect::<Vec<_>>();
+
+            if let Some(action) = untried.choose(rng).copied() {
+                if state.apply(action, rng).is_ok() {
+                    let child = self.nodes.len();
+                    self.nodes.push(Node::new(Some(action), actor));
+                    self.nodes[node].children.push(child);
+                    path.push(child);
+                }
+                break;
+            }
+
+            let available = self.nodes[node]
+                .children
+                .iter()
+                .copied()
+                .filter(|child| {
+                    self.nodes[*child]
+                        .action
+                        .is_some_and(|action| actions.contains(&action))
+                })
+                .collect::<Vec<_>>();
+            for child in &available {
+                self.nodes[*child].available += 1;
+            }
+            let Some(child) = self.select(&available, settings.exploration) else {
+                break;
+            };
+            let Some(action) = self.nodes[child].action else {
+                break;
+            };
+            if state.apply(action, rng).is_err() {
+                break;
+            }
+            node = child;
+            path.push(child);
+        }
+
+        playout(&mut state, settings.playout_depth, policy, rng);
+
+        let rewards = [Side::Player, Side::Ai].map(|side| (side, evaluate(&state, side)));
+        for node in path {
+            let node = &mut self.nodes[node];
+            node.visits += 1;
+            node.reward += rewards
+                .iter()
+                .find(|(side, _)| *side == node.actor)
+                .map_or(0.0, |(_, reward)| *reward);
+        }
+    }
+
+    fn select(&self, children: &[usize], exploration: f64) -> Option<usize> {
+        let score = |child: usize| {
+            let child = &self.nodes[child];
+            let visits = f64::from(child.visits.max(1));
+            let available = f64::from(child.available.max(1));
+            child.reward / visits + exploration * (available.ln() / visits).sqrt()
+        };
+
+        children
+            .iter()
+            .copied()
+            .max_by(|a, b| score(*a).total_cmp(&score(*b)))
+    }
+
+    fn most_visited(&self) -> Option<Action> {
+        let mut best: Option<&Node> = None;
+        for child in &self.nodes[0].children {
+            let child = &self.nodes[*child];
+            if best.is_none_or(|best| child.visits > best.visits) {
+                best = Some(child);
+            }
+        }
+        best?.action
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    use super::{search, search_actions, SearchBudget, SearchSettings};
+    use crate::rules::cards::{CardLibrary, Effect, Keyword};
+    use crate::rules::testing::{creature, spell, Zone};
+    use crate::rules::{Action, GameState, Phase, RuleSet, Side};
+
+    fn settings() -> SearchSettings {
+        SearchSettings {
+            budget: SearchBudget::Iterations(200),
+            ..SearchSettings::default()
+        }
+    }
+
+    // Plays out matches by always doing the first thing it is allowed to
+    fn first_action(state: &GameState, _: Side) -> Vec<Action> {
+        state.legal_actions().into_iter().take(1).collect()
+    }
+
+    #[test]
+    fn takes_a_winning_play() {
+        let mut state = GameState::for_test(RuleSet::default(), CardLibrary::default());
+        state.add_card(Side::Ai, Zone::Hand, creature(2, 2));
+        let finisher = state.add_card(Side::Ai, Zone::Hand, spell(Effect::DamageOpponent(3)));
+        state.set_hp(Side::Player, 3);
+        state.set_turn(Side::Ai, Phase::PlayCreature);
+
+        let chosen = search(
+            &state,
+            Side::Ai,
+            &settings(),
+            &first_action,
+            &mut ChaCha8Rng::seed_from_u64(0),
+        );
+
+        assert_eq!(
+            chosen,
+            Some(Action::Play {
+                card: finisher,
+                target: None,
+            })
+        );
+    }
+
+    #[test]
+    fn blocks_provoking_attackers_first() {
+        let mut state = GameState::for_test(RuleSet::default(), CardLibrary::default());
+        let plain = state.add_card(Side::Player, Zone::Battlefield, creature(3, 1));
+        let provoking = state.add_card(
+            Side::Player,
+            Zone::Battlefield,
+            creature(1, 3).with_keyword(Keyword::Provoke),
+        );
+        let first = state.add_card(Side::Ai, Zone::Battlefield, creature(1, 1));
+        let second = state.add_card(Side::Ai, Zone::Battlefield, creature(1, 1));
+        state.set_turn(Side::Player, Phase::SelectAttackers);
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        for action in [
+            Action::ToggleAttacker(plain),
+            Action::ToggleAttacker(provoking),
+            Action::ConfirmAttackers,
+        ] {
+            assert!(state.apply(action, &mut rng).is_ok());
+        }
+
+        let blocks_plain = |actions: &[Action]| {
+            actions.iter().any(|action| {
+                matches!(
+                    action,
+                    Action::AssignBlocker {
+                        attacker: Some(attacker),
+                        ..
+                    } if *attacker == plain
+                )
+            })
+        };
+        assert!(!blocks_plain(&search_actions(&state)));
+
+        let chosen = search(&state, Side::Ai, &settings(), &first_action, &mut rng);
+        assert!(chosen.is_some_and(|action| state.is_legal(action)));
+        assert!(!blocks_plain(&chosen.into_iter().collect::<Vec<_>>()));
+
+        assert!(state
+            .apply(
+                Action::AssignBlocker {
+                    blocker: first,
+                    attacker: Some(provoking),
+                },
+                &mut rng,
+            )
+            .is_ok());
+        assert_eq!(
+            search_actions(&state),
+            vec![
+                Action::ConfirmBlockers,
+                Action::AssignBlocker {
+                    blocker: second,
+                    attacker: Some(plain),
+                },
+            ]
+        );
+    }
+}
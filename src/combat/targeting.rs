@@ -0,0 +1,92 @@
+use super::hovering::Hovered;
+use super::mirror::{Match, TakeAction};
+use super::{PlayerReference, TurnState, WhosTurnIsIt};
+use crate::prelude::*;
+use crate::rules::cards::TargetRule;
+use crate::rules::{Action, CardRef, Side};
+
+pub struct TargetingPlugin;
+
+#[derive(Component)]
+pub struct ValidTarget;
+
+#[derive(Resource)]
+pub struct PendingTarget {
+    pub card: CardRef,
+    pub rule: TargetRule,
+}
+
+impl Plugin for TargetingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnExit(TurnState::PlayCreature), cancel_targeting);
+        app.add_systems(
+            Update,
+            (
+                handle_target_input.run_if(
+                    resource_exists::<PendingTarget>
+                        .and_then(in_state(TurnState::PlayCreature))
+                        .and_then(in_state(WhosTurnIsIt(PlayerReference::Player))),
+                ),
+                mark_valid_targets,
+            )
+                .chain()
+                .run_if(in_state(MainState::Combat)),
+        );
+    }
+}
+
+fn cancel_targeting(mut commands: Commands) {
+    commands.remove_resource::<PendingTarget>();
+}
+
+fn handle_target_input(
+    mut commands: Commands,
+    pending: Res<PendingTarget>,
+    targets: Query<Entity, (With<ValidTarget>, With<Hovered>)>,
+    game: Res<Match>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    mut actions: EventWriter<TakeAction>,
+) {
+    if mouse.just_pressed(MouseButton::Right) {
+        commands.remove_resource::<PendingTarget>();
+        return;
+    }
+    if !mouse.just_pressed(MouseButton::Left) {
+        return;
+    }
+
+    let Some(target) = targets.iter().find_map(|entity| game.card(entity)) else {
+        return;
+    };
+
+    actions.send(TakeAction {
+        player: PlayerReference::Player,
+        action: Action::Play {
+            card: pending.card,
+            target: Some(target),
+        },
+    });
+    commands.remove_resource::<PendingTarget>();
+}
+
+fn mark_valid_targets(
+    mut commands: Commands,
+    pending: Option<Res<PendingTarget>>,
+    game: Res<Match>,
+    marked: Query<Entity, With<ValidTarget>>,
+) {
+    let valid = pending
+        .map(|pending| game.entities(&game.state().valid_targets(pending.rule, Side::Player)))
+        .unwrap_or_default();
+
+    for card in &marked {
+        if !valid.contains(&card) {
+            commands.entity(card).remove::<ValidTarget>();
+        }
+    }
+    for card in valid {
+        if !marked.contains(card) {
+            commands.entity(card).insert(ValidTarget);
+        }
+    }
+}
@@ -0,0 +1,35 @@
+use super::mirror::Match;
+use crate::prelude::*;
+
+pub struct TokenPlugin;
+
+// A creature that was never in a deck and stops existing once it leaves the field
+#[derive(Component)]
+pub struct Token;
+
+impl Plugin for TokenPlugin {
+    fn build(&self, app: &mut App) {
+        // Runs after everything in Update had a chance to look at the leaving token
+        app.add_systems(
+            PostUpdate,
+            despawn_departed_tokens
+                .run_if(resource_exists_and_changed::<Match>)
+                .run_if(in_state(MainState::Combat)),
+        );
+    }
+}
+
+fn despawn_departed_tokens(
+    mut commands: Commands,
+    game: Res<Match>,
+    tokens: Query<Entity, With<Token>>,
+) {
+    for entity in &tokens {
+        let on_battlefield = game
+            .card(entity)
+            .is_some_and(|card| game.state().on_battlefield(card));
+        if !on_battlefield {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
@@ -1,9 +1,9 @@
-use crate::combat::card::CardInfo;
 use crate::prelude::*;
+use crate::rules::cards::CardId;
 
 #[derive(Resource)]
 pub struct PlayerInfo {
     pub max_hp: u8,
     pub current_hp: u8,
-    pub deck: Vec<CardInfo>,
+    pub deck: Vec<CardId>,
 }
@@ -0,0 +1,48 @@
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, AsyncReadExt, LoadContext};
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::prelude::*;
+use crate::rules::RuleSet;
+
+// Everything about a match that isn't up to the player
+#[derive(Asset, TypePath, Deserialize)]
+pub struct Encounter {
+    // The name of the ai profile the opponent plays with
+    pub opponent: String,
+    #[serde(default)]
+    pub rules: RuleSet,
+}
+
+#[derive(Default)]
+pub struct EncounterLoader;
+
+#[derive(Error, Debug)]
+pub enum EncounterLoadError {
+    #[error("Could not read encounter: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid encounter: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+}
+
+impl AssetLoader for EncounterLoader {
+    type Asset = Encounter;
+    type Settings = ();
+    type Error = EncounterLoadError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(ron::de::from_bytes(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["encounter.ron"]
+    }
+}
@@ -0,0 +1,137 @@
+use bevy::color::palettes::tailwind::{GRAY_800, GRAY_900, GREEN_400, RED_400};
+
+use crate::combat::outcome::MatchResult;
+use crate::data::PlayerInfo;
+use crate::prelude::*;
+
+pub struct GameOverPlugin;
+
+#[derive(Component, Clone, Copy)]
+enum ResultButton {
+    Rematch,
+    Return,
+}
+
+impl Plugin for GameOverPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(MainState::GameOver), spawn_result_screen);
+        app.add_systems(
+            Update,
+            (handle_result_buttons, highlight_result_buttons).run_if(in_state(MainState::GameOver)),
+        );
+    }
+}
+
+fn spawn_result_screen(
+    mut commands: Commands,
+    result: Res<MatchResult>,
+    fonts: Res<assets::Fonts>,
+) {
+    let (title, color) = if result.player_won {
+        ("Victory", GREEN_400)
+    } else {
+        ("Defeat", RED_400)
+    };
+
+    commands
+        .spawn((
+            Name::new("Result Screen"),
+            StateScoped(MainState::GameOver),
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    row_gap: Val::Px(30.0),
+                    ..default()
+                },
+                background_color: GRAY_900.into(),
+                ..default()
+            },
+        ))
+        .with_children(|commands| {
+            commands.spawn(TextBundle::from_section(
+                title,
+                TextStyle {
+                    font: fonts.pixel.clone_weak(),
+                    font_size: 150.0,
+                    color: color.into(),
+                },
+            ));
+            spawn_result_button(commands, &fonts, ResultButton::Rematch, "Rematch");
+            spawn_result_button(commands, &fonts, ResultButton::Return, "Return");
+        });
+}
+
+fn spawn_result_button(
+    commands: &mut ChildBuilder,
+    fonts: &assets::Fonts,
+    button: ResultButton,
+    label: &str,
+) {
+    commands
+        .spawn((
+            button,
+            ButtonBundle {
+                style: Style {
+                    width: Val::Px(300.0),
+                    padding: UiRect::all(Val::Px(10.0)),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                background_color: GRAY_800.into(),
+                ..default()
+            },
+        ))
+        .with_children(|commands| {
+            commands.spawn(TextBundle::from_section(
+                label,
+                TextStyle {
+                    font: fonts.pixel.clone_weak(),
+                    font_size: 50.0,
+                    ..default()
+                },
+            ));
+        });
+}
+
+fn highlight_result_buttons(
+    mut buttons: Query<(&Interaction, &mut BackgroundColor), Changed<Interaction>>,
+) {
+    for (interaction, mut color) in &mut buttons {
+        *color = if *interaction == Interaction::None {
+            GRAY_800.into()
+        } else {
+            GRAY_900.into()
+        };
+    }
+}
+
+fn handle_result_buttons(
+    buttons: Query<(&Interaction, &ResultButton), Changed<Interaction>>,
+    result: Res<MatchResult>,
+    mut player_info: ResMut<PlayerInfo>,
+    mut state: ResMut<NextState<MainState>>,
+) {
+    for (interaction, button) in &buttons {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        match button {
+            ResultButton::Rematch => {
+                player_info.current_hp = result.starting_hp;
+                state.set(MainState::Combat);
+            }
+            ResultButton::Return => {
+                // A defeat ends the run, so the next one starts fresh
+                if player_info.current_hp == 0 {
+                    player_info.current_hp = player_info.max_hp;
+                }
+                state.set(MainState::TestingSetup);
+            }
+        }
+    }
+}
@@ -36,9 +36,15 @@
 )]
 
 mod assets;
+mod card_loader;
 mod combat;
 mod data;
+mod encounter_loader;
+mod game_over;
 mod position;
+mod profile_loader;
+mod rng;
+mod rules;
 
 #[allow(unused_imports)]
 mod prelude {
@@ -54,6 +60,7 @@ pub enum MainState {
     Loading,
     TestingSetup,
     Combat,
+    GameOver,
 }
 
 fn main() {
@@ -71,6 +78,7 @@ fn main() {
     }
 
     app.init_state::<MainState>();
+    app.enable_state_scoped_entities::<MainState>();
 
     app.insert_resource(data::PlayerInfo {
         max_hp: 20,
@@ -80,7 +88,9 @@ fn main() {
     app.add_plugins((
         assets::AssetPlugin,
         combat::CombatPlugin,
+        game_over::GameOverPlugin,
         position::PositionPlugin,
+        rng::RngPlugin,
     ));
 
     // app.add_systems(Update, ());
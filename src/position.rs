@@ -8,14 +8,14 @@ impl Plugin for PositionPlugin {
     }
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq)]
 pub enum AxisAnchor {
     Neg,
     Center,
     Pos,
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq)]
 pub struct RelativeAxis {
     pub anchor: AxisAnchor,
     pub amount: f32,
@@ -31,7 +31,7 @@ impl RelativeAxis {
     }
 }
 
-#[derive(Clone, Copy, Component)]
+#[derive(Clone, Copy, PartialEq, Component)]
 pub struct Relative {
     pub x: Option<RelativeAxis>,
     pub y: Option<RelativeAxis>,
@@ -0,0 +1,77 @@
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, AsyncReadExt, LoadContext};
+use bevy::utils::HashMap;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::combat::ai::{AiDifficulty, AiProfile, AiWeights};
+use crate::prelude::*;
+
+#[derive(Asset, TypePath)]
+pub struct AiProfileCollection(pub HashMap<String, AiProfile>);
+
+// Play styles are shared between profiles, so they are defined once and named
+#[derive(Deserialize)]
+struct ProfileDefinitions {
+    styles: HashMap<String, AiWeights>,
+    profiles: HashMap<String, ProfileDefinition>,
+}
+
+#[derive(Deserialize)]
+struct ProfileDefinition {
+    difficulty: AiDifficulty,
+    style: String,
+}
+
+#[derive(Default)]
+pub struct AiProfileLoader;
+
+#[derive(Error, Debug)]
+pub enum AiProfileLoadError {
+    #[error("Could not read ai profiles: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid ai profiles: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+    #[error("Ai profile {profile:?} uses unknown play style {style:?}")]
+    UnknownStyle { profile: String, style: String },
+}
+
+impl AssetLoader for AiProfileLoader {
+    type Asset = AiProfileCollection;
+    type Settings = ();
+    type Error = AiProfileLoadError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let definitions: ProfileDefinitions = ron::de::from_bytes(&bytes)?;
+
+        let profiles = definitions
+            .profiles
+            .into_iter()
+            .map(|(profile, definition)| {
+                let Some(weights) = definitions.styles.get(&definition.style) else {
+                    return Err(AiProfileLoadError::UnknownStyle {
+                        profile,
+                        style: definition.style,
+                    });
+                };
+                let profile_info = AiProfile {
+                    difficulty: definition.difficulty,
+                    weights: weights.clone(),
+                };
+                Ok((profile, profile_info))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(AiProfileCollection(profiles))
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ai.ron"]
+    }
+}
@@ -0,0 +1,39 @@
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use crate::prelude::*;
+
+pub struct RngPlugin;
+
+// All randomness in the game should come from here,
+// so that running with the same seed plays out the same way
+#[derive(Resource)]
+pub struct GameRng(pub ChaCha8Rng);
+
+impl GameRng {
+    pub fn from_seed(seed: u64) -> Self {
+        Self(ChaCha8Rng::seed_from_u64(seed))
+    }
+}
+
+// The seed each match starts from, without one every match gets a new one
+#[derive(Resource)]
+pub struct GameSeed(pub Option<u64>);
+
+impl Plugin for RngPlugin {
+    fn build(&self, app: &mut App) {
+        let seed = std::env::var("RALLY_RIDERS_SEED")
+            .ok()
+            .and_then(|seed| seed.parse().ok());
+
+        app.insert_resource(GameSeed(seed));
+        app.add_systems(OnEnter(MainState::Combat), seed_rng);
+    }
+}
+
+// Seeded again for every match, so a rematch with the same seed plays out the same way
+pub fn seed_rng(mut commands: Commands, seed: Res<GameSeed>) {
+    let seed = seed.0.unwrap_or_else(rand::random);
+    info!("Using rng seed {seed}");
+    commands.insert_resource(GameRng::from_seed(seed));
+}
@@ -0,0 +1,190 @@
+use rand::Rng;
+use thiserror::Error;
+
+use super::cards::Trigger;
+use super::state::{CardRef, GameState, Phase};
+
+// Everything a player can do, these are the only way the state moves forward
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Action {
+    Play {
+        card: CardRef,
+        target: Option<CardRef>,
+    },
+    // Ends the play phase without playing anything
+    Pass,
+    ToggleAttacker(CardRef),
+    ConfirmAttackers,
+    AssignBlocker {
+        blocker: CardRef,
+        attacker: Option<CardRef>,
+    },
+    ConfirmBlockers,
+    Discard(CardRef),
+}
+
+#[derive(Error, Debug)]
+#[error("{0:?} is not allowed right now")]
+pub struct IllegalAction(pub Action);
+
+impl GameState {
+    pub fn legal_actions(&self) -> Vec<Action> {
+        let Some(side) = self.decider() else {
+            return Vec::new();
+        };
+        let player = self.player(side);
+
+        let mut actions = Vec::new();
+        match self.phase {
+            Phase::PlayCreature => {
+                actions.push(Action::Pass);
+                for card in player.hand.iter().filter(|card| self.is_playable(**card)) {
+                    match self.play_target(*card) {
+                        Some(rule) => {
+                            actions.extend(self.valid_targets(rule, side).into_iter().map(
+                                |target| Action::Play {
+                                    card: *card,
+                                    target: Some(target),
+                                },
+                            ));
+                        }
+                        None => actions.push(Action::Play {
+                            card: *card,
+                            target: None,
+                        }),
+                    }
+                }
+            }
+            Phase::SelectAttackers => {
+                actions.push(Action::ConfirmAttackers);
+                actions.extend(
+                    player
+                        .battlefield
+                        .iter()
+                        .copied()
+                        .map(Action::ToggleAttacker),
+                );
+            }
+            Phase::SelectDefenders => {
+                actions.push(Action::ConfirmBlockers);
+                for blocker in &player.battlefield {
+                    if self.blocks.iter().any(|(other, _)| other == blocker) {
+                        actions.push(Action::AssignBlocker {
+                            blocker: *blocker,
+                            attacker: None,
+                        });
+                    }
+                    let blocked = self.blocked_without(*blocker);
+                    for attacker in &self.attackers {
+                        if self.can_block(*attacker, &blocked) {
+                            actions.push(Action::AssignBlocker {
+                                blocker: *blocker,
+                                attacker: Some(*attacker),
+                            });
+                        }
+                    }
+                }
+            }
+            Phase::Discard => {
+                actions.extend(player.hand.iter().copied().map(Action::Discard));
+            }
+        }
+        actions
+    }
+
+    pub fn is_legal(&self, action: Action) -> bool {
+        let Some(side) = self.decider() else {
+            return false;
+        };
+        let player = self.player(side);
+
+        match (self.phase, action) {
+            (Phase::PlayCreature, Action::Play { card, target }) => {
+                // Anything that isn't in the hand might not be a card at all
+                if !player.hand.contains(&card) {
+                    return false;
+                }
+                let valid_target = match (self.play_target(card), target) {
+                    (Some(rule), Some(target)) => self.valid_targets(rule, side).contains(&target),
+                    (None, None) => true,
+                    _ => false,
+                };
+                self.is_playable(card) && valid_target
+            }
+            (Phase::SelectAttackers, Action::ToggleAttacker(card)) => {
+                player.battlefield.contains(&card)
+            }
+            (Phase::SelectDefenders, Action::AssignBlocker { blocker, attacker }) => {
+                player.battlefield.contains(&blocker)
+                    && attacker.is_none_or(|attacker| {
+                        self.can_block(attacker, &self.blocked_without(blocker))
+                    })
+            }
+            (Phase::Discard, Action::Discard(card)) => player.hand.contains(&card),
+            (Phase::PlayCreature, Action::Pass)
+            | (Phase::SelectAttackers, Action::ConfirmAttackers)
+            | (Phase::SelectDefenders, Action::ConfirmBlockers) => true,
+            _ => false,
+        }
+    }
+
+    // Plays the action out until someone has to decide something again
+    pub fn apply(&mut self, action: Action, rng: &mut impl Rng) -> Result<(), IllegalAction> {
+        if !self.is_legal(action) {
+            return Err(IllegalAction(action));
+        }
+
+        match action {
+            Action::Play { card, target } => {
+                self.play(card, target, rng);
+                if !self.is_over() {
+                    self.phase = Phase::SelectAttackers;
+                }
+            }
+            Action::Pass => self.phase = Phase::SelectAttackers,
+            Action::ToggleAttacker(card) => {
+                if self.attackers.contains(&card) {
+                    self.attackers.retain(|attacker| *attacker != card);
+                } else {
+                    self.attackers.push(card);
+                }
+            }
+            Action::ConfirmAttackers if self.attackers.is_empty() => self.finish_combat(rng),
+            Action::ConfirmAttackers => self.phase = Phase::SelectDefenders,
+            Action::AssignBlocker { blocker, attacker } => {
+                self.blocks.retain(|(other, blocked)| {
+                    *other != blocker && attacker.is_none_or(|attacker| *blocked != attacker)
+                });
+                // Only one blocker per attacker, so a new assignment replaces the old one
+                if let Some(attacker) = attacker {
+                    self.blocks.push((blocker, attacker));
+                }
+                self.drop_unsupported_blocks();
+            }
+            Action::ConfirmBlockers => self.finish_combat(rng),
+            Action::Discard(card) => {
+                self.send_to_graveyard(card);
+                if self.player(self.current).hand.len() <= self.rules.max_hand_size {
+                    self.end_turn(rng);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn play(&mut self, card: CardRef, target: Option<CardRef>, rng: &mut impl Rng) {
+        let side = self.current;
+        let player = self.player_mut(side);
+        player.hand.retain(|other| *other != card);
+
+        // Spells resolve through their play triggers and never stay on the field
+        if self.card(card).is_spell() {
+            self.player_mut(side).graveyard.push(card);
+        } else {
+            self.player_mut(side).battlefield.push(card);
+        }
+
+        self.queue_triggers(side, card, Trigger::Play, target);
+        self.resolve(rng);
+    }
+}
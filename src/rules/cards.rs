@@ -0,0 +1,184 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use super::{CardRef, Side};
+
+// Stays the same across versions, so decks can be saved and compared
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct CardId(pub String);
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Deserialize)]
+pub enum Keyword {
+    Unblockable,
+    Provoke,
+    Trample,
+    Lifelink,
+}
+
+impl Keyword {
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Unblockable => "Unblockable",
+            Self::Provoke => "Provoke",
+            Self::Trample => "Trample",
+            Self::Lifelink => "Lifelink",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug, Deserialize)]
+pub enum CardKind {
+    #[default]
+    Creature,
+    Spell,
+}
+
+#[derive(Clone, Deserialize)]
+pub struct CardGameplayInfo {
+    #[serde(default)]
+    pub kind: CardKind,
+    pub cast_crowd: u8,
+    pub minimum_crowd: u8,
+    #[serde(default)]
+    pub hp: u8,
+    #[serde(default)]
+    pub power: u8,
+    #[serde(default)]
+    pub keywords: Vec<Keyword>,
+    #[serde(default)]
+    pub triggers: Vec<TriggeredEffect>,
+    #[serde(default)]
+    pub auras: Vec<Aura>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Deserialize)]
+pub enum Trigger {
+    Play,
+    Draw,
+    Death,
+    StartOfTurn,
+    EndOfTurn,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct TriggeredEffect {
+    pub trigger: Trigger,
+    pub effect: Effect,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub enum Effect {
+    DamageOpponent(u8),
+    HealController(u8),
+    DrawCards(usize),
+    DamageCreature(u8, TargetRule),
+    ModifyCreature(StatModifier, TargetRule),
+    SummonToken(CardId),
+}
+
+impl Effect {
+    pub const fn target_rule(&self) -> Option<TargetRule> {
+        match self {
+            Self::DamageCreature(_, rule) | Self::ModifyCreature(_, rule) => Some(*rule),
+            _ => None,
+        }
+    }
+
+    pub fn is_harmful(&self) -> bool {
+        match self {
+            Self::DamageOpponent(_) | Self::DamageCreature(..) => true,
+            Self::ModifyCreature(modifier, _) => {
+                i16::from(modifier.power) + i16::from(modifier.hp) < 0
+            }
+            _ => false,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Deserialize)]
+pub enum TargetRule {
+    Any,
+    Enemy,
+    Friendly,
+}
+
+impl TargetRule {
+    pub fn allows(self, controller: Side, owner: Side) -> bool {
+        match self {
+            Self::Any => true,
+            Self::Enemy => owner != controller,
+            Self::Friendly => owner == controller,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Deserialize)]
+pub enum Duration {
+    EndOfTurn,
+    Turns(u8),
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct StatModifier {
+    #[serde(default)]
+    pub power: i8,
+    #[serde(default)]
+    pub hp: i8,
+    pub duration: Duration,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Deserialize)]
+pub enum AuraScope {
+    OtherFriendly,
+    Enemy,
+}
+
+impl AuraScope {
+    pub fn includes(self, source: CardRef, source_owner: Side, card: CardRef, owner: Side) -> bool {
+        match self {
+            Self::OtherFriendly => owner == source_owner && card != source,
+            Self::Enemy => owner != source_owner,
+        }
+    }
+}
+
+// A bonus a creature gives while it is on the battlefield. Power and hp apply
+// to creatures on the battlefield, the casting cost to cards in hand.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Aura {
+    pub affects: AuraScope,
+    #[serde(default)]
+    pub power: i8,
+    #[serde(default)]
+    pub hp: i8,
+    #[serde(default)]
+    pub cast_crowd: i8,
+}
+
+// A card as the rules see it, shared by every copy of it in a match
+pub struct CardRules {
+    pub id: CardId,
+    pub gameplay: CardGameplayInfo,
+}
+
+// Every card a match can use, including the tokens effects summon
+#[derive(Default)]
+pub struct CardLibrary(HashMap<CardId, Arc<CardRules>>);
+
+impl CardLibrary {
+    pub fn new(cards: impl IntoIterator<Item = (CardId, CardGameplayInfo)>) -> Self {
+        Self(
+            cards
+                .into_iter()
+                .map(|(id, gameplay)| (id.clone(), Arc::new(CardRules { id, gameplay })))
+                .collect(),
+        )
+    }
+
+    pub fn get(&self, id: &CardId) -> Option<&Arc<CardRules>> {
+        self.0.get(id)
+    }
+}
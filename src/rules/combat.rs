@@ -0,0 +1,140 @@
+use rand::Rng;
+
+use super::cards::{Keyword, Trigger};
+use super::state::{CardRef, GameState, Phase};
+use super::HealRule;
+
+impl GameState {
+    pub fn can_block(&self, attacker: CardRef, blocked: &[CardRef]) -> bool {
+        if !self.attackers.contains(&attacker) || self.card(attacker).has(Keyword::Unblockable) {
+            return false;
+        }
+
+        // Provoking attackers have to be blocked before anything else can be
+        self.card(attacker).has(Keyword::Provoke)
+            || self.attackers.iter().all(|other| {
+                let other_card = self.card(*other);
+                !other_card.has(Keyword::Provoke)
+                    || other_card.has(Keyword::Unblockable)
+                    || blocked.contains(other)
+            })
+    }
+
+    // The attackers that are blocked by anything other than this blocker
+    pub fn blocked_without(&self, blocker: CardRef) -> Vec<CardRef> {
+        self.blocks
+            .iter()
+            .filter(|(other, _)| *other != blocker)
+            .map(|(_, attacker)| *attacker)
+            .collect()
+    }
+
+    // Taking a blocker off a provoking attacker can leave blocks behind that were
+    // only allowed because of it, those are taken back as well
+    pub(super) fn drop_unsupported_blocks(&mut self) {
+        while let Some(index) = self.blocks.iter().position(|(blocker, attacker)| {
+            !self.can_block(*attacker, &self.blocked_without(*blocker))
+        }) {
+            self.blocks.remove(index);
+        }
+    }
+
+    // Everything after the blockers are chosen happens on its own, up to the next turn
+    pub(super) fn finish_combat(&mut self, rng: &mut impl Rng) {
+        self.resolve_combat();
+        self.resolve(rng);
+        if self.is_over() {
+            return;
+        }
+        self.heal_creatures();
+        self.end_turn(rng);
+    }
+
+    fn resolve_combat(&mut self) {
+        let attacking_player = self.current;
+        let defender = attacking_player.other();
+
+        // All damage is dealt at the same time, so collect it before applying any of it
+        let mut damage = Vec::new();
+        let mut to_defender: u8 = 0;
+        let mut healing = [(attacking_player, 0_u8), (defender, 0_u8)];
+        for attacker in &self.attackers {
+            let power = self.power(*attacker);
+            let keywords = self.card(*attacker);
+            let blocker = self
+                .blocks
+                .iter()
+                .find(|(_, blocked)| blocked == attacker)
+                .map(|(blocker, _)| *blocker);
+
+            if let Some(blocker) = blocker {
+                let blocker_power = self.power(blocker);
+                damage.push((blocker, power));
+                damage.push((*attacker, blocker_power));
+
+                if keywords.has(Keyword::Trample) {
+                    to_defender =
+                        to_defender.saturating_add(power.saturating_sub(self.hp(blocker)));
+                }
+                if self.card(blocker).has(Keyword::Lifelink) {
+                    healing[1].1 = healing[1].1.saturating_add(blocker_power);
+                }
+            } else {
+                to_defender = to_defender.saturating_add(power);
+            }
+
+            if keywords.has(Keyword::Lifelink) {
+                healing[0].1 = healing[0].1.saturating_add(power);
+            }
+        }
+
+        for (card, amount) in damage {
+            let card = &mut self.cards[card.0];
+            card.damage = card.damage.saturating_add(amount);
+        }
+        let defending = self.player_mut(defender);
+        defending.hp = defending.hp.saturating_sub(to_defender);
+        for (side, amount) in healing {
+            let player = self.player_mut(side);
+            player.hp = player.hp.saturating_add(amount).min(player.max_hp);
+        }
+
+        self.attackers.clear();
+        self.blocks.clear();
+    }
+
+    fn heal_creatures(&mut self) {
+        let heal = self.rules.heal;
+        for player in &self.players {
+            for card in &player.battlefield {
+                let card = &mut self.cards[card.0];
+                card.damage = match heal {
+                    HealRule::Full => 0,
+                    HealRule::Amount(amount) => card.damage.saturating_sub(amount),
+                    HealRule::None => card.damage,
+                };
+            }
+        }
+    }
+
+    // A player with too many cards discards first, then the turn goes over to the other player
+    pub(super) fn end_turn(&mut self, rng: &mut impl Rng) {
+        let side = self.current;
+        if self.player(side).hand.len() > self.rules.max_hand_size {
+            self.phase = Phase::Discard;
+            return;
+        }
+
+        for card in self.player(side).battlefield.clone() {
+            self.queue_triggers(side, card, Trigger::EndOfTurn, None);
+        }
+        self.expire_modifiers();
+        self.resolve(rng);
+        if self.is_over() {
+            return;
+        }
+
+        self.current = side.other();
+        self.start_turn(rng);
+    }
+}
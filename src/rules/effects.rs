@@ -0,0 +1,175 @@
+use std::sync::Arc;
+
+use rand::Rng;
+
+use super::cards::{Effect, Trigger};
+use super::state::{CardRef, GameState};
+use super::{GameEvent, Side};
+
+// A triggered ability waiting to resolve, looked up on its card when it does
+#[derive(Clone)]
+pub struct PendingTrigger {
+    controller: Side,
+    card: CardRef,
+    index: usize,
+    target: Option<CardRef>,
+}
+
+impl GameState {
+    pub(super) fn queue_triggers(
+        &mut self,
+        controller: Side,
+        card: CardRef,
+        trigger: Trigger,
+        target: Option<CardRef>,
+    ) {
+        let triggers = &self.cards[card.0].rules.gameplay.triggers;
+        self.queue.extend(
+            triggers
+                .iter()
+                .enumerate()
+                .filter(|(_, triggered)| triggered.trigger == trigger)
+                .map(|(index, _)| PendingTrigger {
+                    controller,
+                    card,
+                    index,
+                    target,
+                }),
+        );
+    }
+
+    // Triggers that happen at the same time resolve with the active player's first,
+    // then in the order they were queued. Anything the effects cause, like drawing
+    // or dying, queues its own triggers which resolve in a later batch.
+    // Only played cards get their targets chosen, the rest are picked automatically.
+    pub(super) fn resolve(&mut self, rng: &mut impl Rng) {
+        loop {
+            self.settle_board();
+            if self.queue.is_empty() {
+                break;
+            }
+
+            let mut batch = std::mem::take(&mut self.queue);
+            let current = self.current;
+            batch.sort_by_key(|trigger| trigger.controller != current);
+            for trigger in batch {
+                self.resolve_trigger(trigger, rng);
+            }
+        }
+        self.check_for_defeat();
+    }
+
+    fn resolve_trigger(&mut self, trigger: PendingTrigger, rng: &mut impl Rng) {
+        let rules = Arc::clone(&self.card(trigger.card).rules);
+        let effect = &rules.gameplay.triggers[trigger.index].effect;
+        let target = trigger
+            .target
+            .or_else(|| self.pick_target(effect, trigger.controller));
+        self.apply_effect(trigger.controller, effect, target, rng);
+    }
+
+    // Harmful effects go for the strongest enemy, helpful ones for the strongest friend.
+    // If only the wrong side can be targeted, the weakest creature there gets it instead.
+    pub fn pick_target(&self, effect: &Effect, controller: Side) -> Option<CardRef> {
+        let rule = effect.target_rule()?;
+        let wanted_owner = if effect.is_harmful() {
+            controller.other()
+        } else {
+            controller
+        };
+
+        self.valid_targets(rule, controller)
+            .into_iter()
+            .max_by_key(|card| {
+                let power = self.power(*card);
+                if self.card(*card).owner == wanted_owner {
+                    (true, power)
+                } else {
+                    (false, u8::MAX - power)
+                }
+            })
+    }
+
+    fn apply_effect(
+        &mut self,
+        controller: Side,
+        effect: &Effect,
+        target: Option<CardRef>,
+        rng: &mut impl Rng,
+    ) {
+        // The target might have left the battlefield before the effect resolved
+        let target = target.filter(|card| self.on_battlefield(*card));
+
+        match effect {
+            Effect::DamageOpponent(amount) => {
+                let opponent = self.player_mut(controller.other());
+                opponent.hp = opponent.hp.saturating_sub(*amount);
+            }
+            Effect::HealController(amount) => {
+                let player = self.player_mut(controller);
+                player.hp = player.hp.saturating_add(*amount).min(player.max_hp);
+            }
+            Effect::DrawCards(amount) => self.draw(controller, *amount, rng),
+            Effect::DamageCreature(amount, _) => {
+                if let Some(card) = target {
+                    let card = &mut self.cards[card.0];
+                    card.damage = card.damage.saturating_add(*amount);
+                }
+            }
+            Effect::ModifyCreature(modifier, _) => {
+                if let Some(card) = target {
+                    self.cards[card.0].modifiers.push(modifier.clone());
+                }
+            }
+            Effect::SummonToken(card) => self.summon_token(controller, card),
+        }
+    }
+
+    // Creatures without hp die, then every departure shrinks the crowd, which can
+    // make more creatures leave. They leave one at a time, highest minimum first
+    // and newest first on ties, so the same board always ends up the same way.
+    // Anything leaving can take an aura with it, so this repeats until nothing changes.
+    fn settle_board(&mut self) {
+        loop {
+            let dead = Side::BOTH
+                .into_iter()
+                .flat_map(|side| {
+                    self.player(side)
+                        .battlefield
+                        .iter()
+                        .filter(|card| self.hp(**card) == 0)
+                        .map(move |card| (side, *card))
+                })
+                .collect::<Vec<_>>();
+            let mut changed = !dead.is_empty();
+            for (side, card) in dead {
+                self.send_to_graveyard(card);
+                self.queue_triggers(side, card, Trigger::Death, None);
+            }
+
+            for side in Side::BOTH {
+                while let Some(card) = self.unsupported_creature(side) {
+                    self.send_to_graveyard(card);
+                    self.events.push(GameEvent::Abandoned(card));
+                    changed = true;
+                }
+            }
+
+            if !changed {
+                break;
+            }
+        }
+    }
+
+    fn unsupported_creature(&self, side: Side) -> Option<CardRef> {
+        let battlefield = &self.player(side).battlefield;
+        let crowd = battlefield.len();
+        battlefield
+            .iter()
+            .enumerate()
+            .map(|(index, card)| (index, *card, self.card(*card).gameplay().minimum_crowd))
+            .filter(|(_, _, minimum)| usize::from(*minimum) > crowd)
+            .max_by_key(|(index, _, minimum)| (*minimum, *index))
+            .map(|(_, card, _)| card)
+    }
+}
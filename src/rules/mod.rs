@@ -0,0 +1,90 @@
+// The rules of a match, without anything from bevy. The state can be cloned and
+// played forward on its own, the combat plugins only show it and feed it actions.
+
+pub use actions::Action;
+use serde::Deserialize;
+pub use state::{CardRef, CardState, GameState, Phase, PlayerSetup};
+
+mod actions;
+pub mod cards;
+mod combat;
+mod effects;
+mod state;
+#[cfg(test)]
+pub mod testing;
+#[cfg(test)]
+mod tests;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default, Debug)]
+pub enum Side {
+    #[default]
+    Player,
+    Ai,
+}
+
+impl Side {
+    pub const BOTH: [Self; 2] = [Self::Player, Self::Ai];
+
+    pub const fn other(self) -> Self {
+        match self {
+            Self::Player => Self::Ai,
+            Self::Ai => Self::Player,
+        }
+    }
+
+    const fn index(self) -> usize {
+        match self {
+            Self::Player => 0,
+            Self::Ai => 1,
+        }
+    }
+}
+
+#[derive(Default, Clone, Copy, PartialEq, Eq, Debug, Deserialize)]
+pub enum HealRule {
+    #[default]
+    Full,
+    Amount(u8),
+    None,
+}
+
+#[derive(Default, Clone, Copy, PartialEq, Eq, Debug, Deserialize)]
+pub enum EmptyDeckRule {
+    #[default]
+    Fatigue,
+    Lose,
+    Reshuffle,
+}
+
+// Each match can play by its own rules, anything a data file leaves out keeps the default
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(default)]
+pub struct RuleSet {
+    pub heal: HealRule,
+    pub empty_deck: EmptyDeckRule,
+    pub max_hand_size: usize,
+}
+
+impl Default for RuleSet {
+    fn default() -> Self {
+        Self {
+            heal: HealRule::default(),
+            empty_deck: EmptyDeckRule::default(),
+            max_hand_size: 7,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum EmptyDeckResult {
+    Fatigue(u8),
+    Lost,
+    Reshuffled(usize),
+}
+
+// Things that happened which can't be read off the state afterwards
+#[derive(Clone, Copy, Debug)]
+pub enum GameEvent {
+    DeckEmpty { side: Side, result: EmptyDeckResult },
+    Abandoned(CardRef),
+}
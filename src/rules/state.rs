@@ -0,0 +1,484 @@
+use std::sync::Arc;
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use super::cards::{
+    Aura,
+    CardGameplayInfo,
+    CardId,
+    CardKind,
+    CardLibrary,
+    CardRules,
+    Duration,
+    Keyword,
+    StatModifier,
+    TargetRule,
+    Trigger,
+};
+use super::effects::PendingTrigger;
+use super::{EmptyDeckResult, EmptyDeckRule, GameEvent, RuleSet, Side};
+
+const OPENING_HAND_SIZE: usize = 7;
+
+// A card in the match, by its position in the state
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct CardRef(pub usize);
+
+#[derive(Clone)]
+pub struct CardState {
+    pub rules: Arc<CardRules>,
+    pub owner: Side,
+    // Tokens stop existing once they leave the battlefield
+    pub token: bool,
+    // Stays on the card when its hp changes, until it is healed
+    pub damage: u8,
+    pub modifiers: Vec<StatModifier>,
+}
+
+impl CardState {
+    const fn new(rules: Arc<CardRules>, owner: Side, token: bool) -> Self {
+        Self {
+            rules,
+            owner,
+            token,
+            damage: 0,
+            modifiers: Vec::new(),
+        }
+    }
+
+    pub fn gameplay(&self) -> &CardGameplayInfo {
+        &self.rules.gameplay
+    }
+
+    pub fn is_spell(&self) -> bool {
+        self.gameplay().kind == CardKind::Spell
+    }
+
+    pub fn has(&self, keyword: Keyword) -> bool {
+        self.gameplay().keywords.contains(&keyword)
+    }
+}
+
+#[derive(Clone)]
+pub struct PlayerState {
+    pub hp: u8,
+    pub max_hp: u8,
+    pub deck: Vec<CardRef>,
+    pub hand: Vec<CardRef>,
+    pub battlefield: Vec<CardRef>,
+    pub graveyard: Vec<CardRef>,
+    pub fatigue: u8,
+    pub lost: bool,
+}
+
+impl PlayerState {
+    // Every creature on the battlefield is part of the crowd
+    pub fn crowd(&self) -> u8 {
+        u8::try_from(self.battlefield.len()).unwrap_or(u8::MAX)
+    }
+}
+
+// The points where someone has to decide something, the rest of a turn plays out on its own
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Phase {
+    PlayCreature,
+    SelectAttackers,
+    SelectDefenders,
+    Discard,
+}
+
+pub struct PlayerSetup {
+    pub hp: u8,
+    pub max_hp: u8,
+    pub deck: Vec<CardId>,
+}
+
+#[derive(Clone)]
+pub struct GameState {
+    pub(super) library: Arc<CardLibrary>,
+    pub(super) rules: RuleSet,
+    pub(super) cards: Vec<CardState>,
+    pub(super) players: [PlayerState; 2],
+    pub(super) current: Side,
+    pub(super) phase: Phase,
+    pub(super) attackers: Vec<CardRef>,
+    // Each blocker with the attacker it blocks
+    pub(super) blocks: Vec<(CardRef, CardRef)>,
+    // Only holds anything while an action is being applied
+    pub(super) queue: Vec<PendingTrigger>,
+    pub(super) events: Vec<GameEvent>,
+}
+
+impl GameState {
+    // Cards the library doesn't know are left out of the decks
+    pub fn new(
+        library: Arc<CardLibrary>,
+        setups: [PlayerSetup; 2],
+        rules: RuleSet,
+        rng: &mut impl Rng,
+    ) -> Self {
+        let mut cards = Vec::new();
+        let [player, ai] = setups;
+        let players = [(Side::Player, player), (Side::Ai, ai)].map(|(side, setup)| {
+            let deck = setup
+                .deck
+                .iter()
+                .filter_map(|id| library.get(id))
+                .map(|rules| {
+                    cards.push(CardState::new(Arc::clone(rules), side, false));
+                    CardRef(cards.len() - 1)
+                })
+                .collect();
+            PlayerState {
+                hp: setup.hp,
+                max_hp: setup.max_hp,
+                deck,
+                hand: Vec::new(),
+                battlefield: Vec::new(),
+                graveyard: Vec::new(),
+                fatigue: 0,
+                lost: false,
+            }
+        });
+
+        let mut state = Self {
+            library,
+            rules,
+            cards,
+            players,
+            current: Side::Player,
+            phase: Phase::PlayCreature,
+            attackers: Vec::new(),
+            blocks: Vec::new(),
+            queue: Vec::new(),
+            events: Vec::new(),
+        };
+
+        // Always shuffle in the same order so a seed gives the same decks
+        for side in Side::BOTH {
+            state.player_mut(side).deck.shuffle(rng);
+        }
+        for side in Side::BOTH {
+            state.draw(side, OPENING_HAND_SIZE, rng);
+        }
+        state.start_turn(rng);
+        state
+    }
+
+    pub fn cards(&self) -> &[CardState] {
+        &self.cards
+    }
+
+    pub fn card(&self, card: CardRef) -> &CardState {
+        &self.cards[card.0]
+    }
+
+    pub const fn player(&self, side: Side) -> &PlayerState {
+        &self.players[side.index()]
+    }
+
+    pub(super) const fn player_mut(&mut self, side: Side) -> &mut PlayerState {
+        &mut self.players[side.index()]
+    }
+
+    pub const fn rules(&self) -> &RuleSet {
+        &self.rules
+    }
+
+    pub const fn current(&self) -> Side {
+        self.current
+    }
+
+    pub const fn phase(&self) -> Phase {
+        self.phase
+    }
+
+    pub fn attackers(&self) -> &[CardRef] {
+        &self.attackers
+    }
+
+    pub fn blocks(&self) -> &[(CardRef, CardRef)] {
+        &self.blocks
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.players.iter().any(|player| player.lost)
+    }
+
+    // If both players lose at the same time the player loses
+    pub const fn winner(&self) -> Option<Side> {
+        if self.player(Side::Player).lost {
+            Some(Side::Ai)
+        } else if self.player(Side::Ai).lost {
+            Some(Side::Player)
+        } else {
+            None
+        }
+    }
+
+    // Who has to act next, nobody once the match is over
+    pub fn decider(&self) -> Option<Side> {
+        if self.is_over() {
+            return None;
+        }
+        Some(match self.phase {
+            Phase::SelectDefenders => self.current.other(),
+            _ => self.current,
+        })
+    }
+
+    pub fn events(&self) -> &[GameEvent] {
+        &self.events
+    }
+
+    pub fn take_events(&mut self) -> Vec<GameEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    // A copy of the match as the viewer might imagine it. The other side's hand and deck
+    // are dealt again from the cards that could be in them, and both decks are shuffled.
+    // Cards only change places while they are hidden, so nothing the viewer saw changes.
+    pub fn determinize(&self, viewer: Side, rng: &mut impl Rng) -> Self {
+        let mut state = self.clone();
+        state.events.clear();
+
+        let opponent = state.player(viewer.other());
+        let hidden = opponent
+            .hand
+            .iter()
+            .chain(&opponent.deck)
+            .copied()
+            .collect::<Vec<_>>();
+        let mut rules = hidden
+            .iter()
+            .map(|card| Arc::clone(&state.cards[card.0].rules))
+            .collect::<Vec<_>>();
+        rules.shuffle(rng);
+        for (card, rules) in hidden.into_iter().zip(rules) {
+            state.cards[card.0].rules = rules;
+        }
+
+        state.player_mut(viewer).deck.shuffle(rng);
+        state
+    }
+
+    pub fn on_battlefield(&self, card: CardRef) -> bool {
+        self.player(self.card(card).owner)
+            .battlefield
+            .contains(&card)
+    }
+
+    pub fn in_hand(&self, card: CardRef) -> bool {
+        self.player(self.card(card).owner).hand.contains(&card)
+    }
+
+    // Sums up what the auras of every creature on the battlefield give this card
+    fn aura_bonus(&self, card: CardRef, amount: impl Fn(&Aura) -> i8) -> i16 {
+        let owner = self.card(card).owner;
+        let mut total = 0;
+        for side in Side::BOTH {
+            for source in &self.player(side).battlefield {
+                for aura in &self.card(*source).gameplay().auras {
+                    if aura.affects.includes(*source, side, card, owner) {
+                        total += i16::from(amount(aura));
+                    }
+                }
+            }
+        }
+        total
+    }
+
+    // Stats only change on the battlefield, auras don't reach cards anywhere else
+    fn stat(&self, card: CardRef, base: u8, modifier: impl Fn(&Aura) -> i8, own: i16) -> u8 {
+        let aura = if self.on_battlefield(card) {
+            self.aura_bonus(card, modifier)
+        } else {
+            0
+        };
+        apply_bonus(base, own + aura)
+    }
+
+    pub fn power(&self, card: CardRef) -> u8 {
+        let state = self.card(card);
+        let bonus = state.modifiers.iter().map(|m| i16::from(m.power)).sum();
+        self.stat(card, state.gameplay().power, |aura| aura.power, bonus)
+    }
+
+    pub fn max_hp(&self, card: CardRef) -> u8 {
+        let state = self.card(card);
+        let bonus = state.modifiers.iter().map(|m| i16::from(m.hp)).sum();
+        self.stat(card, state.gameplay().hp, |aura| aura.hp, bonus)
+    }
+
+    pub fn hp(&self, card: CardRef) -> u8 {
+        self.max_hp(card).saturating_sub(self.card(card).damage)
+    }
+
+    pub fn cast_cost(&self, card: CardRef) -> u8 {
+        let base = self.card(card).gameplay().cast_crowd;
+        if self.in_hand(card) {
+            apply_bonus(base, self.aura_bonus(card, |aura| aura.cast_crowd))
+        } else {
+            base
+        }
+    }
+
+    pub fn can_afford(&self, card: CardRef) -> bool {
+        self.can_afford_with_crowd(card, self.player(self.card(card).owner).crowd())
+    }
+
+    pub fn can_afford_with_crowd(&self, card: CardRef, crowd: u8) -> bool {
+        let state = self.card(card);
+        // A creature counts towards the crowd once it is on the battlefield
+        let joins_crowd = u8::from(!state.is_spell());
+        self.cast_cost(card) <= crowd
+            && state.gameplay().minimum_crowd <= crowd.saturating_add(joins_crowd)
+    }
+
+    // The rule for the target a card needs when it is played, if any
+    pub fn play_target(&self, card: CardRef) -> Option<TargetRule> {
+        self.card(card)
+            .gameplay()
+            .triggers
+            .iter()
+            .filter(|triggered| triggered.trigger == Trigger::Play)
+            .find_map(|triggered| triggered.effect.target_rule())
+    }
+
+    // Whether the card could be played if it was its owners turn to play
+    pub fn is_playable(&self, card: CardRef) -> bool {
+        let owner = self.card(card).owner;
+        let has_target = self
+            .play_target(card)
+            .is_none_or(|rule| !self.valid_targets(rule, owner).is_empty());
+        self.in_hand(card) && has_target && self.can_afford(card)
+    }
+
+    pub fn valid_targets(&self, rule: TargetRule, controller: Side) -> Vec<CardRef> {
+        Side::BOTH
+            .into_iter()
+            .filter(|side| rule.allows(controller, *side))
+            .flat_map(|side| self.player(side).battlefield.iter().copied())
+            .collect()
+    }
+
+    pub(super) fn start_turn(&mut self, rng: &mut impl Rng) {
+        let side = self.current;
+        for card in self.player(side).battlefield.clone() {
+            self.queue_triggers(side, card, Trigger::StartOfTurn, None);
+        }
+        self.draw(side, 1, rng);
+        self.resolve(rng);
+        self.phase = Phase::PlayCreature;
+    }
+
+    pub(super) fn draw(&mut self, side: Side, amount: usize, rng: &mut impl Rng) {
+        let mut missing = 0;
+        for _ in 0..amount {
+            let Some(card) = self.player_mut(side).deck.pop() else {
+                missing += 1;
+                continue;
+            };
+            self.player_mut(side).hand.push(card);
+            self.queue_triggers(side, card, Trigger::Draw, None);
+        }
+
+        if missing > 0 {
+            self.run_out_of_cards(side, missing, rng);
+        }
+    }
+
+    fn run_out_of_cards(&mut self, side: Side, missing: usize, rng: &mut impl Rng) {
+        match self.rules.empty_deck {
+            EmptyDeckRule::Fatigue => {
+                // Every missing card hurts one more than the last
+                for _ in 0..missing {
+                    let player = self.player_mut(side);
+                    player.fatigue = player.fatigue.saturating_add(1);
+                    player.hp = player.hp.saturating_sub(player.fatigue);
+                    let result = EmptyDeckResult::Fatigue(player.fatigue);
+                    self.events.push(GameEvent::DeckEmpty { side, result });
+                }
+            }
+            EmptyDeckRule::Lose => {
+                self.player_mut(side).lost = true;
+                self.events.push(GameEvent::DeckEmpty {
+                    side,
+                    result: EmptyDeckResult::Lost,
+                });
+            }
+            EmptyDeckRule::Reshuffle => {
+                let graveyard = std::mem::take(&mut self.player_mut(side).graveyard);
+                let reshuffled = graveyard.len();
+                for card in &graveyard {
+                    self.cards[card.0].damage = 0;
+                }
+                let deck = &mut self.player_mut(side).deck;
+                deck.extend(graveyard);
+                deck.shuffle(rng);
+
+                self.events.push(GameEvent::DeckEmpty {
+                    side,
+                    result: EmptyDeckResult::Reshuffled(reshuffled),
+                });
+                if reshuffled > 0 {
+                    self.draw(side, missing, rng);
+                }
+            }
+        }
+    }
+
+    pub(super) fn summon_token(&mut self, side: Side, id: &CardId) {
+        let Some(rules) = self.library.get(id) else {
+            return;
+        };
+        self.cards
+            .push(CardState::new(Arc::clone(rules), side, true));
+        let card = CardRef(self.cards.len() - 1);
+        self.player_mut(side).battlefield.push(card);
+    }
+
+    // Removes the card from wherever it is, it ends up in the graveyard unless it is a token
+    pub(super) fn send_to_graveyard(&mut self, card: CardRef) {
+        let state = &mut self.cards[card.0];
+        state.modifiers.clear();
+        let (owner, token) = (state.owner, state.token);
+
+        self.attackers.retain(|attacker| *attacker != card);
+        self.blocks
+            .retain(|(blocker, attacker)| *blocker != card && *attacker != card);
+
+        let player = self.player_mut(owner);
+        player.battlefield.retain(|other| *other != card);
+        player.hand.retain(|other| *other != card);
+        if !token {
+            player.graveyard.push(card);
+        }
+    }
+
+    pub(super) fn expire_modifiers(&mut self) {
+        for card in &mut self.cards {
+            card.modifiers
+                .retain_mut(|modifier| match &mut modifier.duration {
+                    Duration::EndOfTurn => false,
+                    Duration::Turns(turns) => {
+                        *turns = turns.saturating_sub(1);
+                        *turns > 0
+                    }
+                });
+        }
+    }
+
+    pub(super) fn check_for_defeat(&mut self) {
+        for player in &mut self.players {
+            if player.hp == 0 {
+                player.lost = true;
+            }
+        }
+    }
+}
+
+fn apply_bonus(base: u8, bonus: i16) -> u8 {
+    u8::try_from((i16::from(base) + bonus).max(0)).unwrap_or(u8::MAX)
+}
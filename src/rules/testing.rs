@@ -0,0 +1,138 @@
+// Builds matches card by card, so tests can start from any board they need
+
+use std::sync::Arc;
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use super::cards::{
+    CardGameplayInfo,
+    CardId,
+    CardKind,
+    CardLibrary,
+    CardRules,
+    Effect,
+    Keyword,
+    Trigger,
+    TriggeredEffect,
+};
+use super::state::{CardState, PlayerState};
+use super::{CardRef, GameState, Phase, RuleSet, Side};
+
+const FILLER_CARDS: usize = 5;
+
+#[derive(Clone, Copy)]
+pub enum Zone {
+    Deck,
+    Hand,
+    Battlefield,
+    Graveyard,
+}
+
+pub fn rng() -> ChaCha8Rng {
+    ChaCha8Rng::seed_from_u64(0)
+}
+
+pub fn creature(power: u8, hp: u8) -> CardGameplayInfo {
+    CardGameplayInfo {
+        kind: CardKind::Creature,
+        cast_crowd: 0,
+        minimum_crowd: 0,
+        hp,
+        power,
+        keywords: Vec::new(),
+        triggers: Vec::new(),
+        auras: Vec::new(),
+    }
+}
+
+pub fn spell(trigger: Effect) -> CardGameplayInfo {
+    CardGameplayInfo {
+        kind: CardKind::Spell,
+        ..creature(0, 0)
+    }
+    .with_trigger(Trigger::Play, trigger)
+}
+
+impl CardGameplayInfo {
+    pub fn with_keyword(mut self, keyword: Keyword) -> Self {
+        self.keywords.push(keyword);
+        self
+    }
+
+    pub fn with_trigger(mut self, trigger: Trigger, effect: Effect) -> Self {
+        self.triggers.push(TriggeredEffect { trigger, effect });
+        self
+    }
+
+    pub const fn with_costs(mut self, cast_crowd: u8, minimum_crowd: u8) -> Self {
+        self.cast_crowd = cast_crowd;
+        self.minimum_crowd = minimum_crowd;
+        self
+    }
+}
+
+impl GameState {
+    // Both players start at 20 hp with a few plain creatures in their deck, so
+    // turns can pass without anyone running out of cards
+    pub fn for_test(rules: RuleSet, library: CardLibrary) -> Self {
+        let player = PlayerState {
+            hp: 20,
+            max_hp: 20,
+            deck: Vec::new(),
+            hand: Vec::new(),
+            battlefield: Vec::new(),
+            graveyard: Vec::new(),
+            fatigue: 0,
+            lost: false,
+        };
+        let mut state = Self {
+            library: Arc::new(library),
+            rules,
+            cards: Vec::new(),
+            players: [player.clone(), player],
+            current: Side::Player,
+            phase: Phase::PlayCreature,
+            attackers: Vec::new(),
+            blocks: Vec::new(),
+            queue: Vec::new(),
+            events: Vec::new(),
+        };
+        for side in Side::BOTH {
+            for _ in 0..FILLER_CARDS {
+                state.add_card(side, Zone::Deck, creature(1, 1));
+            }
+        }
+        state
+    }
+
+    pub fn add_card(&mut self, owner: Side, zone: Zone, gameplay: CardGameplayInfo) -> CardRef {
+        let id = CardId(format!("test_card_{}", self.cards.len()));
+        self.cards.push(CardState {
+            rules: Arc::new(CardRules { id, gameplay }),
+            owner,
+            token: false,
+            damage: 0,
+            modifiers: Vec::new(),
+        });
+        let card = CardRef(self.cards.len() - 1);
+
+        let player = self.player_mut(owner);
+        match zone {
+            Zone::Deck => player.deck.push(card),
+            Zone::Hand => player.hand.push(card),
+            Zone::Battlefield => player.battlefield.push(card),
+            Zone::Graveyard => player.graveyard.push(card),
+        }
+        card
+    }
+
+    pub fn set_turn(&mut self, current: Side, phase: Phase) {
+        self.current = current;
+        self.phase = phase;
+    }
+
+    pub const fn set_hp(&mut self, side: Side, hp: u8) {
+        self.player_mut(side).hp = hp;
+    }
+}
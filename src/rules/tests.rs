@@ -0,0 +1,657 @@
+use std::sync::Arc;
+
+use rand::seq::SliceRandom;
+
+use super::cards::{
+    CardId,
+    CardLibrary,
+    Duration,
+    Effect,
+    Keyword,
+    StatModifier,
+    TargetRule,
+    Trigger,
+};
+use super::testing::{creature, rng, spell, Zone};
+use super::{
+    Action,
+    CardRef,
+    EmptyDeckResult,
+    EmptyDeckRule,
+    GameEvent,
+    GameState,
+    HealRule,
+    Phase,
+    PlayerSetup,
+    RuleSet,
+    Side,
+};
+
+fn new_state() -> GameState {
+    GameState::for_test(RuleSet::default(), CardLibrary::default())
+}
+
+fn apply(state: &mut GameState, action: Action) {
+    let result = state.apply(action, &mut rng());
+    assert!(result.is_ok(), "{action:?} should be allowed");
+}
+
+fn in_graveyard(state: &GameState, card: CardRef) -> bool {
+    state
+        .player(state.card(card).owner)
+        .graveyard
+        .contains(&card)
+}
+
+// Declares the attacks and blocks, then lets the player's combat play out
+fn fight(state: &mut GameState, attackers: &[CardRef], blocks: &[(CardRef, CardRef)]) {
+    state.set_turn(Side::Player, Phase::SelectAttackers);
+    for attacker in attackers {
+        apply(state, Action::ToggleAttacker(*attacker));
+    }
+    apply(state, Action::ConfirmAttackers);
+    for (blocker, attacker) in blocks {
+        apply(
+            state,
+            Action::AssignBlocker {
+                blocker: *blocker,
+                attacker: Some(*attacker),
+            },
+        );
+    }
+    apply(state, Action::ConfirmBlockers);
+}
+
+#[test]
+fn play_phase_offers_affordable_cards_and_their_targets() {
+    let mut state = new_state();
+    let cheap = state.add_card(Side::Player, Zone::Hand, creature(1, 1));
+    let expensive = state.add_card(Side::Player, Zone::Hand, creature(5, 5).with_costs(3, 0));
+    let bolt = state.add_card(
+        Side::Player,
+        Zone::Hand,
+        spell(Effect::DamageCreature(2, TargetRule::Enemy)),
+    );
+    let friend = state.add_card(Side::Player, Zone::Battlefield, creature(1, 1));
+    let enemy = state.add_card(Side::Ai, Zone::Battlefield, creature(1, 1));
+
+    let actions = state.legal_actions();
+    assert!(actions.contains(&Action::Pass));
+    assert!(actions.contains(&Action::Play {
+        card: cheap,
+        target: None,
+    }));
+    assert!(actions.contains(&Action::Play {
+        card: bolt,
+        target: Some(enemy),
+    }));
+    assert!(!actions.contains(&Action::Play {
+        card: bolt,
+        target: Some(friend),
+    }));
+    assert!(!actions
+        .iter()
+        .any(|action| matches!(action, Action::Play { card, .. } if *card == expensive)));
+    assert_eq!(actions.len(), 3);
+}
+
+#[test]
+fn attack_phase_offers_every_creature() {
+    let mut state = new_state();
+    let first = state.add_card(Side::Player, Zone::Battlefield, creature(1, 1));
+    let second = state.add_card(Side::Player, Zone::Battlefield, creature(1, 1));
+    state.add_card(Side::Ai, Zone::Battlefield, creature(1, 1));
+    state.set_turn(Side::Player, Phase::SelectAttackers);
+
+    assert_eq!(
+        state.legal_actions(),
+        vec![
+            Action::ConfirmAttackers,
+            Action::ToggleAttacker(first),
+            Action::ToggleAttacker(second),
+        ]
+    );
+}
+
+#[test]
+fn defend_phase_is_decided_by_the_defender() {
+    let mut state = new_state();
+    let attacker = state.add_card(Side::Player, Zone::Battlefield, creature(1, 1));
+    let sneaky = state.add_card(
+        Side::Player,
+        Zone::Battlefield,
+        creature(1, 1).with_keyword(Keyword::Unblockable),
+    );
+    let blocker = state.add_card(Side::Ai, Zone::Battlefield, creature(1, 1));
+    state.set_turn(Side::Player, Phase::SelectAttackers);
+    apply(&mut state, Action::ToggleAttacker(attacker));
+    apply(&mut state, Action::ToggleAttacker(sneaky));
+    apply(&mut state, Action::ConfirmAttackers);
+
+    assert_eq!(state.phase(), Phase::SelectDefenders);
+    assert_eq!(state.decider(), Some(Side::Ai));
+    assert_eq!(
+        state.legal_actions(),
+        vec![
+            Action::ConfirmBlockers,
+            Action::AssignBlocker {
+                blocker,
+                attacker: Some(attacker),
+            },
+        ]
+    );
+
+    apply(
+        &mut state,
+        Action::AssignBlocker {
+            blocker,
+            attacker: Some(attacker),
+        },
+    );
+    assert!(state.legal_actions().contains(&Action::AssignBlocker {
+        blocker,
+        attacker: None,
+    }));
+}
+
+#[test]
+fn provoking_attackers_are_blocked_first() {
+    let mut state = new_state();
+    let plain = state.add_card(Side::Player, Zone::Battlefield, creature(1, 1));
+    let provoking = state.add_card(
+        Side::Player,
+        Zone::Battlefield,
+        creature(1, 1).with_keyword(Keyword::Provoke),
+    );
+    let first = state.add_card(Side::Ai, Zone::Battlefield, creature(1, 1));
+    let second = state.add_card(Side::Ai, Zone::Battlefield, creature(1, 1));
+    state.set_turn(Side::Player, Phase::SelectAttackers);
+    apply(&mut state, Action::ToggleAttacker(plain));
+    apply(&mut state, Action::ToggleAttacker(provoking));
+    apply(&mut state, Action::ConfirmAttackers);
+
+    let block_plain = Action::AssignBlocker {
+        blocker: second,
+        attacker: Some(plain),
+    };
+    assert!(!state.legal_actions().contains(&block_plain));
+    assert!(state.apply(block_plain, &mut rng()).is_err());
+
+    apply(
+        &mut state,
+        Action::AssignBlocker {
+            blocker: first,
+            attacker: Some(provoking),
+        },
+    );
+    assert!(state.legal_actions().contains(&block_plain));
+}
+
+#[test]
+fn unblocking_a_provoking_attacker_takes_back_the_blocks_it_allowed() {
+    let mut state = new_state();
+    let plain = state.add_card(Side::Player, Zone::Battlefield, creature(1, 1));
+    let provoking = state.add_card(
+        Side::Player,
+        Zone::Battlefield,
+        creature(1, 1).with_keyword(Keyword::Provoke),
+    );
+    let first = state.add_card(Side::Ai, Zone::Battlefield, creature(1, 1));
+    let second = state.add_card(Side::Ai, Zone::Battlefield, creature(1, 1));
+    state.set_turn(Side::Player, Phase::SelectAttackers);
+    apply(&mut state, Action::ToggleAttacker(plain));
+    apply(&mut state, Action::ToggleAttacker(provoking));
+    apply(&mut state, Action::ConfirmAttackers);
+
+    apply(
+        &mut state,
+        Action::AssignBlocker {
+            blocker: first,
+            attacker: Some(provoking),
+        },
+    );
+    apply(
+        &mut state,
+        Action::AssignBlocker {
+            blocker: second,
+            attacker: Some(plain),
+        },
+    );
+    apply(
+        &mut state,
+        Action::AssignBlocker {
+            blocker: first,
+            attacker: None,
+        },
+    );
+
+    assert!(state.blocks().is_empty());
+}
+
+#[test]
+fn discard_phase_offers_every_card_in_hand() {
+    let mut state = new_state();
+    let first = state.add_card(Side::Player, Zone::Hand, creature(1, 1));
+    let second = state.add_card(Side::Player, Zone::Hand, creature(1, 1));
+    state.set_turn(Side::Player, Phase::Discard);
+
+    assert_eq!(
+        state.legal_actions(),
+        vec![Action::Discard(first), Action::Discard(second)]
+    );
+}
+
+#[test]
+fn nothing_is_legal_once_the_match_is_over() {
+    let mut state = new_state();
+    state.add_card(Side::Player, Zone::Hand, creature(1, 1));
+    state.set_hp(Side::Ai, 0);
+    state.check_for_defeat();
+
+    assert!(state.player(Side::Ai).lost);
+    assert_eq!(state.decider(), None);
+    assert!(state.legal_actions().is_empty());
+    assert!(state.apply(Action::Pass, &mut rng()).is_err());
+}
+
+#[test]
+fn illegal_actions_are_rejected_without_changing_anything() {
+    let mut state = new_state();
+    let own = state.add_card(Side::Player, Zone::Hand, creature(1, 1));
+    let theirs = state.add_card(Side::Ai, Zone::Hand, creature(1, 1));
+    let expensive = state.add_card(Side::Player, Zone::Hand, creature(5, 5).with_costs(3, 0));
+    let bolt = state.add_card(
+        Side::Player,
+        Zone::Hand,
+        spell(Effect::DamageCreature(2, TargetRule::Any)),
+    );
+    let enemy = state.add_card(Side::Ai, Zone::Battlefield, creature(1, 1));
+
+    let illegal = [
+        Action::ToggleAttacker(enemy),
+        Action::ConfirmBlockers,
+        Action::Discard(own),
+        Action::Play {
+            card: theirs,
+            target: None,
+        },
+        Action::Play {
+            card: expensive,
+            target: None,
+        },
+        Action::Play {
+            card: own,
+            target: Some(enemy),
+        },
+        Action::Play {
+            card: bolt,
+            target: None,
+        },
+        Action::Play {
+            card: CardRef(999),
+            target: None,
+        },
+    ];
+    for action in illegal {
+        assert!(!state.is_legal(action), "{action:?} should not be allowed");
+        assert!(state.apply(action, &mut rng()).is_err());
+    }
+    assert_eq!(state.phase(), Phase::PlayCreature);
+    assert_eq!(state.player(Side::Player).hand.len(), 3);
+}
+
+#[test]
+fn unblocked_attackers_hit_the_defender() {
+    let mut state = new_state();
+    let attacker = state.add_card(Side::Player, Zone::Battlefield, creature(3, 1));
+
+    fight(&mut state, &[attacker], &[]);
+
+    assert_eq!(state.player(Side::Ai).hp, 17);
+    assert_eq!(state.current(), Side::Ai);
+}
+
+#[test]
+fn blocked_creatures_damage_each_other() {
+    let mut state = new_state();
+    let attacker = state.add_card(Side::Player, Zone::Battlefield, creature(2, 2));
+    let blocker = state.add_card(Side::Ai, Zone::Battlefield, creature(2, 3));
+
+    fight(&mut state, &[attacker], &[(blocker, attacker)]);
+
+    assert_eq!(state.player(Side::Ai).hp, 20);
+    assert!(in_graveyard(&state, attacker));
+    assert!(state.on_battlefield(blocker));
+}
+
+#[test]
+fn trample_carries_leftover_damage_through() {
+    let mut state = new_state();
+    let attacker = state.add_card(
+        Side::Player,
+        Zone::Battlefield,
+        creature(5, 5).with_keyword(Keyword::Trample),
+    );
+    let blocker = state.add_card(Side::Ai, Zone::Battlefield, creature(1, 2));
+
+    fight(&mut state, &[attacker], &[(blocker, attacker)]);
+
+    assert_eq!(state.player(Side::Ai).hp, 17);
+    assert!(in_graveyard(&state, blocker));
+}
+
+#[test]
+fn lifelink_heals_whoever_controls_it() {
+    let mut state = new_state();
+    let attacker = state.add_card(
+        Side::Player,
+        Zone::Battlefield,
+        creature(3, 5).with_keyword(Keyword::Lifelink),
+    );
+    let blocker = state.add_card(
+        Side::Ai,
+        Zone::Battlefield,
+        creature(2, 5).with_keyword(Keyword::Lifelink),
+    );
+    state.set_hp(Side::Player, 10);
+    state.set_hp(Side::Ai, 19);
+
+    fight(&mut state, &[attacker], &[(blocker, attacker)]);
+
+    assert_eq!(state.player(Side::Player).hp, 13);
+    // Healing never goes above the maximum
+    assert_eq!(state.player(Side::Ai).hp, 20);
+}
+
+#[test]
+fn heal_rules_decide_what_damage_stays() {
+    for (heal, damage_left) in [
+        (HealRule::Full, 0),
+        (HealRule::Amount(1), 2),
+        (HealRule::None, 3),
+    ] {
+        let rules = RuleSet {
+            heal,
+            ..RuleSet::default()
+        };
+        let mut state = GameState::for_test(rules, CardLibrary::default());
+        let attacker = state.add_card(Side::Player, Zone::Battlefield, creature(3, 1));
+        let blocker = state.add_card(Side::Ai, Zone::Battlefield, creature(1, 5));
+
+        fight(&mut state, &[attacker], &[(blocker, attacker)]);
+
+        assert_eq!(state.card(blocker).damage, damage_left, "{heal:?}");
+    }
+}
+
+#[test]
+fn creatures_abandon_a_shrinking_crowd_one_at_a_time() {
+    let mut state = new_state();
+    let doomed = state.add_card(Side::Player, Zone::Battlefield, creature(1, 1));
+    let needs_two = state.add_card(
+        Side::Player,
+        Zone::Battlefield,
+        creature(1, 1).with_costs(0, 2),
+    );
+    let needs_three = state.add_card(
+        Side::Player,
+        Zone::Battlefield,
+        creature(1, 1).with_costs(0, 3),
+    );
+    let stays = state.add_card(Side::Ai, Zone::Battlefield, creature(1, 1).with_costs(0, 1));
+
+    state.cards[doomed.0].damage = 1;
+    state.resolve(&mut rng());
+
+    // The highest minimum leaves first, which is what makes the next one leave
+    let abandoned = state
+        .events()
+        .iter()
+        .filter_map(|event| match event {
+            GameEvent::Abandoned(card) => Some(*card),
+            GameEvent::DeckEmpty { .. } => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(abandoned, vec![needs_three, needs_two]);
+    assert!(state.player(Side::Player).battlefield.is_empty());
+    assert!([doomed, needs_two, needs_three]
+        .iter()
+        .all(|card| in_graveyard(&state, *card)));
+    assert!(state.on_battlefield(stays));
+}
+
+#[test]
+fn triggers_of_the_active_player_resolve_first() {
+    let mut state = new_state();
+    // Hitting first and healing after leaves the ai at full hp, the other way around it isn't
+    let attacker = state.add_card(
+        Side::Player,
+        Zone::Battlefield,
+        creature(2, 2).with_trigger(Trigger::Death, Effect::DamageOpponent(5)),
+    );
+    let blocker = state.add_card(
+        Side::Ai,
+        Zone::Battlefield,
+        creature(2, 2).with_trigger(Trigger::Death, Effect::HealController(5)),
+    );
+
+    fight(&mut state, &[attacker], &[(blocker, attacker)]);
+
+    assert_eq!(state.player(Side::Ai).hp, 20);
+}
+
+#[test]
+fn triggers_of_one_card_resolve_in_order() {
+    let first = CardId("first".to_owned());
+    let second = CardId("second".to_owned());
+    let library = CardLibrary::new([
+        (first.clone(), creature(1, 1)),
+        (second.clone(), creature(2, 2)),
+    ]);
+    let mut state = GameState::for_test(RuleSet::default(), library);
+    let summoner = state.add_card(
+        Side::Player,
+        Zone::Hand,
+        creature(0, 1)
+            .with_trigger(Trigger::Play, Effect::SummonToken(first.clone()))
+            .with_trigger(Trigger::Play, Effect::SummonToken(second.clone())),
+    );
+
+    apply(
+        &mut state,
+        Action::Play {
+            card: summoner,
+            target: None,
+        },
+    );
+
+    let ids = state
+        .player(Side::Player)
+        .battlefield
+        .iter()
+        .map(|card| state.card(*card).rules.id.clone())
+        .collect::<Vec<_>>();
+    assert_eq!(ids[1..], [first, second]);
+    assert!(state.card(state.player(Side::Player).battlefield[1]).token);
+}
+
+#[test]
+fn forced_targets_fall_back_to_the_weakest_creature() {
+    let mut state = new_state();
+    let weak = state.add_card(Side::Player, Zone::Battlefield, creature(1, 3));
+    let strong = state.add_card(Side::Player, Zone::Battlefield, creature(4, 3));
+    let harm = Effect::DamageCreature(1, TargetRule::Any);
+    let buff = Effect::ModifyCreature(
+        StatModifier {
+            power: 1,
+            hp: 1,
+            duration: Duration::EndOfTurn,
+        },
+        TargetRule::Any,
+    );
+
+    assert_eq!(state.pick_target(&harm, Side::Ai), Some(strong));
+    assert_eq!(state.pick_target(&buff, Side::Player), Some(strong));
+    assert_eq!(state.pick_target(&harm, Side::Player), Some(weak));
+    assert_eq!(state.pick_target(&buff, Side::Ai), Some(weak));
+}
+
+#[test]
+fn too_many_cards_are_discarded_before_the_turn_ends() {
+    let rules = RuleSet {
+        max_hand_size: 2,
+        ..RuleSet::default()
+    };
+    let mut state = GameState::for_test(rules, CardLibrary::default());
+    let first = state.add_card(Side::Player, Zone::Hand, creature(1, 1));
+    state.add_card(Side::Player, Zone::Hand, creature(1, 1));
+    state.add_card(Side::Player, Zone::Hand, creature(1, 1));
+
+    state.set_turn(Side::Player, Phase::SelectAttackers);
+    apply(&mut state, Action::ConfirmAttackers);
+    assert_eq!(state.phase(), Phase::Discard);
+    assert_eq!(state.decider(), Some(Side::Player));
+
+    apply(&mut state, Action::Discard(first));
+    assert!(in_graveyard(&state, first));
+    assert_eq!(state.current(), Side::Ai);
+    assert_eq!(state.phase(), Phase::PlayCreature);
+}
+
+fn empty_deck_state(empty_deck: EmptyDeckRule) -> GameState {
+    let rules = RuleSet {
+        empty_deck,
+        ..RuleSet::default()
+    };
+    let mut state = GameState::for_test(rules, CardLibrary::default());
+    state.player_mut(Side::Player).deck.clear();
+    state
+}
+
+fn deck_results(state: &GameState) -> Vec<EmptyDeckResult> {
+    state
+        .events()
+        .iter()
+        .filter_map(|event| match event {
+            GameEvent::DeckEmpty { result, .. } => Some(*result),
+            GameEvent::Abandoned(_) => None,
+        })
+        .collect()
+}
+
+#[test]
+fn fatigue_hurts_more_for_every_missing_card() {
+    let mut state = empty_deck_state(EmptyDeckRule::Fatigue);
+
+    state.draw(Side::Player, 2, &mut rng());
+
+    assert_eq!(state.player(Side::Player).hp, 17);
+    assert!(matches!(
+        deck_results(&state)[..],
+        [EmptyDeckResult::Fatigue(1), EmptyDeckResult::Fatigue(2)]
+    ));
+}
+
+#[test]
+fn drawing_from_an_empty_deck_can_lose_the_match() {
+    let mut state = empty_deck_state(EmptyDeckRule::Lose);
+
+    state.draw(Side::Player, 1, &mut rng());
+
+    assert!(matches!(deck_results(&state)[..], [EmptyDeckResult::Lost]));
+    assert!(state.player(Side::Player).lost);
+}
+
+#[test]
+fn the_graveyard_is_reshuffled_into_an_empty_deck() {
+    let mut state = empty_deck_state(EmptyDeckRule::Reshuffle);
+    let first = state.add_card(Side::Player, Zone::Graveyard, creature(1, 3));
+    let second = state.add_card(Side::Player, Zone::Graveyard, creature(1, 3));
+    state.cards[first.0].damage = 2;
+
+    state.draw(Side::Player, 1, &mut rng());
+
+    let player = state.player(Side::Player);
+    assert!(matches!(
+        deck_results(&state)[..],
+        [EmptyDeckResult::Reshuffled(2)]
+    ));
+    assert!(player.graveyard.is_empty());
+    assert_eq!(player.hand.len() + player.deck.len(), 2);
+    assert!([first, second]
+        .iter()
+        .all(|card| state.card(*card).damage == 0));
+}
+
+#[test]
+fn reshuffling_an_empty_graveyard_draws_nothing() {
+    let mut state = empty_deck_state(EmptyDeckRule::Reshuffle);
+
+    state.draw(Side::Player, 1, &mut rng());
+
+    assert!(matches!(
+        deck_results(&state)[..],
+        [EmptyDeckResult::Reshuffled(0)]
+    ));
+    assert!(state.player(Side::Player).hand.is_empty());
+    assert!(!state.is_over());
+}
+
+// Plays whole matches with random legal actions, every one of them has to be accepted
+#[test]
+fn random_matches_only_offer_legal_actions() {
+    let ids = ["small", "big", "bolt"].map(|id| CardId(id.to_owned()));
+    let library = CardLibrary::new([
+        (ids[0].clone(), creature(1, 2)),
+        (ids[1].clone(), creature(3, 3).with_costs(1, 1)),
+        (
+            ids[2].clone(),
+            spell(Effect::DamageCreature(2, TargetRule::Enemy)),
+        ),
+    ]);
+    let library = Arc::new(library);
+
+    for empty_deck in [
+        EmptyDeckRule::Fatigue,
+        EmptyDeckRule::Lose,
+        EmptyDeckRule::Reshuffle,
+    ] {
+        let mut rng = rng();
+        let rules = RuleSet {
+            empty_deck,
+            ..RuleSet::default()
+        };
+        let deck = ids.iter().cycle().take(12).cloned().collect::<Vec<_>>();
+        let setup = || PlayerSetup {
+            hp: 10,
+            max_hp: 10,
+            deck: deck.clone(),
+        };
+        let mut state = GameState::new(Arc::clone(&library), [setup(), setup()], rules, &mut rng);
+
+        for _ in 0..2000 {
+            let actions = state.legal_actions();
+            let Some(action) = actions.choose(&mut rng).copied() else {
+                break;
+            };
+            assert!(state.apply(action, &mut rng).is_ok(), "{action:?}");
+        }
+        assert!(state.is_over(), "{empty_deck:?} should end the match");
+    }
+}
+
+#[test]
+fn end_of_turn_triggers_resolve_before_the_turn_passes() {
+    let mut state = new_state();
+    state.add_card(
+        Side::Player,
+        Zone::Battlefield,
+        creature(1, 1).with_trigger(Trigger::EndOfTurn, Effect::DrawCards(1)),
+    );
+
+    state.set_turn(Side::Player, Phase::SelectAttackers);
+    apply(&mut state, Action::ConfirmAttackers);
+
+    assert_eq!(state.player(Side::Player).hand.len(), 1);
+    assert_eq!(state.current(), Side::Ai);
+}